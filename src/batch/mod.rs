@@ -0,0 +1,98 @@
+use std::{fmt, sync::Arc};
+
+use futures::{Stream, StreamExt, future::BoxFuture, stream};
+
+use crate::Error;
+
+#[cfg(test)]
+mod test;
+
+type Fetch<I, T> = Arc<dyn Fn(I) -> BoxFuture<'static, Result<T, Error>> + Send + Sync>;
+
+/// Many lookups run concurrently, built with `Powo::lookup_many` or
+/// `Ipni::lookup_names`.
+///
+/// Each id is paired with its own result, so one failure doesn't stop the
+/// rest. Results come back in the order the ids were given unless
+/// `ordered(false)` is set, in which case they come as they complete.
+///
+/// ```no_run
+/// # use futures::StreamExt;
+/// # use rustkew::{Api, ipni::Ipni};
+/// # async fn run() -> Result<(), rustkew::Error> {
+/// let ids = vec!["320035-2".parse()?, "320036-2".parse()?];
+/// let mut results = Ipni::new().lookup_names(ids).concurrency(4).stream();
+///
+/// while let Some((id, res)) = results.next().await {
+///   match res {
+///     Ok(citation) => println!("{}: {}", id, citation.name),
+///     Err(err) => eprintln!("{}: {}", id, err),
+///   }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct Batch<I, T> {
+  ids: Vec<I>,
+  fetch: Fetch<I, T>,
+  concurrency: usize,
+  ordered: bool,
+}
+
+impl<I, T> Batch<I, T>
+where
+  I: Clone + Send + 'static,
+  T: Send + 'static,
+{
+  pub(crate) fn new(
+    ids: impl IntoIterator<Item = I>,
+    fetch: impl Fn(I) -> BoxFuture<'static, Result<T, Error>> + Send + Sync + 'static,
+  ) -> Self {
+    Self {
+      ids: ids.into_iter().collect(),
+      fetch: Arc::new(fetch),
+      concurrency: 8,
+      ordered: true,
+    }
+  }
+
+  /// How many lookups may be in flight at once. Defaults to 8, and is at
+  /// least 1.
+  pub fn concurrency(mut self, concurrency: usize) -> Self {
+    self.concurrency = concurrency.max(1);
+
+    self
+  }
+
+  /// Whether results keep the order of the ids, which can hold back finished
+  /// results behind a slow one. Defaults to `true`.
+  pub fn ordered(mut self, ordered: bool) -> Self {
+    self.ordered = ordered;
+
+    self
+  }
+
+  pub fn stream(self) -> impl Stream<Item = (I, Result<T, Error>)> + Send + 'static {
+    let fetch = self.fetch;
+    let lookups = stream::iter(self.ids).map(move |id| {
+      let res = fetch(id.clone());
+      async move { (id, res.await) }
+    });
+
+    if self.ordered {
+      lookups.buffered(self.concurrency).left_stream()
+    } else {
+      lookups.buffer_unordered(self.concurrency).right_stream()
+    }
+  }
+}
+
+impl<I: fmt::Debug, T> fmt::Debug for Batch<I, T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Batch")
+      .field("ids", &self.ids)
+      .field("concurrency", &self.concurrency)
+      .field("ordered", &self.ordered)
+      .finish_non_exhaustive()
+  }
+}
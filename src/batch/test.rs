@@ -0,0 +1,29 @@
+use futures::StreamExt;
+
+use crate::{KewClient, ipni::NameId, transport::MemoryTransport};
+
+fn client() -> KewClient {
+  let transport = MemoryTransport::new().json(
+    "/api/1/n/320035-2",
+    include_str!("../../fixtures/ipni/lookup_name_320035-2.json"),
+  );
+
+  KewClient::builder().transport(transport).build().unwrap()
+}
+
+#[tokio::test]
+async fn lookup_names() {
+  let ids = ["1-1", "320035-2"].map(|id| id.parse::<NameId>().unwrap());
+  let mut res = client()
+    .ipni()
+    .lookup_names(ids)
+    .ordered(false)
+    .stream()
+    .collect::<Vec<_>>()
+    .await;
+  res.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+  assert_eq!(res.len(), 2);
+  assert!(res[0].1.is_err());
+  assert_eq!(res[1].1.as_ref().unwrap().name, "Poa annua");
+}
@@ -0,0 +1,353 @@
+use std::{
+  collections::{BTreeMap, HashMap},
+  fs,
+  io,
+  path::PathBuf,
+  sync::{
+    Mutex,
+    atomic::{AtomicU64, Ordering},
+  },
+  time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+  Error,
+  transport::{HttpResponse, RecordedResponse, url_key},
+};
+
+#[cfg(test)]
+mod test;
+
+/// The kind of endpoint a response came from, which decides how long it is
+/// cached for.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Endpoint {
+  /// A single record, e.g. `taxon/...` or `n/...`. These rarely change.
+  Lookup,
+  /// `search` and `suggest`, whose results change as records are added.
+  Search,
+}
+
+impl Endpoint {
+  pub(crate) fn of(method: &str) -> Endpoint {
+    match method {
+      "search" | "suggest" => Endpoint::Search,
+      _ => Endpoint::Lookup,
+    }
+  }
+}
+
+/// Counts since the cache was created. Expired entries count as misses.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct CacheStats {
+  pub hits: u64,
+  pub misses: u64,
+  /// Responses that couldn't be written to disk. They are still returned,
+  /// and kept in memory if there is room.
+  pub write_errors: u64,
+}
+
+/// Successful responses kept for reuse, set with `KewClientBuilder::cache`.
+///
+/// Responses are keyed on their URL, with the query parameters sorted, and
+/// kept in a bounded in-memory LRU, on disk, or both. The in-memory store is
+/// checked first, and entries found on disk are copied into it. Entries
+/// older than the TTL for their endpoint are treated as missing.
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use rustkew::{Cache, Endpoint, KewClient};
+/// # fn main() -> Result<(), rustkew::Error> {
+/// let cache = Cache::new()
+///   .memory(10_000)
+///   .disk("kew-cache")
+///   .ttl(Endpoint::Search, Duration::from_secs(60));
+/// let client = KewClient::builder().cache(cache).build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Cache {
+  memory: Mutex<Lru>,
+  disk: Option<PathBuf>,
+  lookup_ttl: Duration,
+  search_ttl: Duration,
+  hits: AtomicU64,
+  misses: AtomicU64,
+  write_errors: AtomicU64,
+}
+
+impl Default for Cache {
+  fn default() -> Self {
+    Self {
+      memory: Mutex::new(Lru::new(1024)),
+      disk: None,
+      lookup_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+      search_ttl: Duration::from_secs(10 * 60),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+      write_errors: AtomicU64::new(0),
+    }
+  }
+}
+
+impl Cache {
+  /// An in-memory cache of 1024 responses, keeping lookups for a week and
+  /// searches for ten minutes.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// How many responses to keep in memory. `0` keeps none, for a cache that
+  /// only uses the disk.
+  pub fn memory(self, capacity: usize) -> Self {
+    Self {
+      memory: Mutex::new(Lru::new(capacity)),
+      ..self
+    }
+  }
+
+  /// Also keeps responses in `dir`, one file each, so they survive restarts.
+  /// The directory is created on first write.
+  pub fn disk(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.disk = Some(dir.into());
+
+    self
+  }
+
+  /// How long responses from `endpoint` stay fresh.
+  pub fn ttl(mut self, endpoint: Endpoint, ttl: Duration) -> Self {
+    match endpoint {
+      Endpoint::Lookup => self.lookup_ttl = ttl,
+      Endpoint::Search => self.search_ttl = ttl,
+    }
+
+    self
+  }
+
+  pub fn stats(&self) -> CacheStats {
+    CacheStats {
+      hits: self.hits.load(Ordering::Relaxed),
+      misses: self.misses.load(Ordering::Relaxed),
+      write_errors: self.write_errors.load(Ordering::Relaxed),
+    }
+  }
+
+  /// Drops the response for `url`, wherever it is stored.
+  pub fn invalidate(&self, url: &Url) -> Result<(), Error> {
+    let key = url_key(url);
+    self.memory.lock().unwrap().remove(&key);
+
+    if let Some(dir) = &self.disk {
+      ignore_missing(fs::remove_file(dir.join(file_name(&key))))?;
+    }
+
+    Ok(())
+  }
+
+  /// Drops every response.
+  pub fn clear(&self) -> Result<(), Error> {
+    self.memory.lock().unwrap().clear();
+
+    if let Some(dir) = &self.disk {
+      let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+      };
+
+      for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+          ignore_missing(fs::remove_file(path))?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  /// The fresh response for `url`, counting a hit or a miss. Without an
+  /// endpoint, as when offline, any stored response will do however old it
+  /// is.
+  pub(crate) fn get(&self, endpoint: Option<Endpoint>, url: &Url) -> Option<HttpResponse> {
+    let ttl = endpoint.map(|endpoint| match endpoint {
+      Endpoint::Lookup => self.lookup_ttl,
+      Endpoint::Search => self.search_ttl,
+    });
+    let is_fresh = |entry: &Entry| {
+      let age = SystemTime::now()
+        .duration_since(entry.stored_at)
+        .unwrap_or_default();
+
+      ttl.is_none_or(|ttl| age < ttl)
+    };
+
+    // The lock isn't held while reading from disk.
+    let key = url_key(url);
+    let cached = self.memory.lock().unwrap().get(&key).filter(is_fresh);
+    let entry = cached.or_else(|| {
+      let entry = self.read(&key).filter(is_fresh)?;
+      self.memory.lock().unwrap().put(key, entry.clone());
+
+      Some(entry)
+    });
+
+    let counter = match entry {
+      Some(_) => &self.hits,
+      None => &self.misses,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+
+    entry.map(|entry| entry.response)
+  }
+
+  /// Stores `response` for `url`. The cache is only an optimisation, so a
+  /// failed write is counted in `CacheStats::write_errors` rather than
+  /// failing the request.
+  pub(crate) fn put(&self, url: &Url, response: &HttpResponse) {
+    let key = url_key(url);
+    let entry = Entry {
+      stored_at: SystemTime::now(),
+      response: response.clone(),
+    };
+
+    if self.write(&key, &entry).is_err() {
+      self.write_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    self.memory.lock().unwrap().put(key, entry);
+  }
+
+  fn write(&self, key: &str, entry: &Entry) -> io::Result<()> {
+    let Some(dir) = &self.disk else {
+      return Ok(());
+    };
+
+    let stored = StoredEntry {
+      key: key.to_owned(),
+      stored_at: entry
+        .stored_at
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs(),
+      response: (&entry.response).into(),
+    };
+
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(file_name(key)), serde_json::to_vec(&stored)?)
+  }
+
+  /// Reads an entry from disk. Unreadable entries are treated as missing, so
+  /// a damaged file is simply fetched again.
+  fn read(&self, key: &str) -> Option<Entry> {
+    let file = fs::read(self.disk.as_ref()?.join(file_name(key))).ok()?;
+    let stored: StoredEntry = serde_json::from_slice(&file).ok()?;
+
+    // Guards against two keys sharing a file name.
+    if stored.key != key {
+      return None;
+    }
+
+    Some(Entry {
+      stored_at: UNIX_EPOCH + Duration::from_secs(stored.stored_at),
+      response: (&stored.response).try_into().ok()?,
+    })
+  }
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+  stored_at: SystemTime,
+  response: HttpResponse,
+}
+
+/// An entry as written to disk. `stored_at` is in seconds since the Unix
+/// epoch.
+#[derive(Debug, Deserialize, Serialize)]
+struct StoredEntry {
+  key: String,
+  stored_at: u64,
+  response: RecordedResponse,
+}
+
+/// Entries stamped with when they were last used, so the least recently used
+/// one is first in `order`.
+#[derive(Debug)]
+struct Lru {
+  capacity: usize,
+  tick: u64,
+  entries: HashMap<String, (Entry, u64)>,
+  order: BTreeMap<u64, String>,
+}
+
+impl Lru {
+  fn new(capacity: usize) -> Self {
+    Self {
+      capacity,
+      tick: 0,
+      entries: HashMap::new(),
+      order: BTreeMap::new(),
+    }
+  }
+
+  fn get(&mut self, key: &str) -> Option<Entry> {
+    let (entry, used) = self.entries.get_mut(key)?;
+    self.order.remove(used);
+    self.tick += 1;
+    *used = self.tick;
+    self.order.insert(self.tick, key.to_owned());
+
+    Some(entry.clone())
+  }
+
+  fn put(&mut self, key: String, entry: Entry) {
+    if self.capacity == 0 {
+      return;
+    }
+
+    self.remove(&key);
+    while self.entries.len() >= self.capacity {
+      let Some((_, oldest)) = self.order.pop_first() else {
+        break;
+      };
+      self.entries.remove(&oldest);
+    }
+
+    self.tick += 1;
+    self.order.insert(self.tick, key.clone());
+    self.entries.insert(key, (entry, self.tick));
+  }
+
+  fn remove(&mut self, key: &str) {
+    if let Some((_, used)) = self.entries.remove(key) {
+      self.order.remove(&used);
+    }
+  }
+
+  fn clear(&mut self) {
+    self.entries.clear();
+    self.order.clear();
+  }
+}
+
+/// A file name for `key`, from its 64-bit FNV-1a hash. This doesn't depend on
+/// the standard library's hasher, which may change between releases and
+/// would orphan every file on disk.
+fn file_name(key: &str) -> String {
+  let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, b| {
+    (hash ^ b as u64).wrapping_mul(0x100000001b3)
+  });
+
+  format!("{:016x}.json", hash)
+}
+
+fn ignore_missing(result: io::Result<()>) -> io::Result<()> {
+  match result {
+    Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+    result => result,
+  }
+}
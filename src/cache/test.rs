@@ -0,0 +1,236 @@
+use std::time::{Duration, SystemTime};
+
+use reqwest::StatusCode;
+
+use super::{Entry, Lru};
+use crate::{
+  Cache,
+  CacheStats,
+  Endpoint,
+  Error,
+  KewClient,
+  transport::{HttpResponse, MemoryTransport},
+};
+
+fn transport() -> MemoryTransport {
+  MemoryTransport::new()
+    .json(
+      "/api/1/a/12653-1",
+      include_str!("../../fixtures/ipni/lookup_author_12653-1.json"),
+    )
+    .json(
+      "/api/1/n/320035-2",
+      include_str!("../../fixtures/ipni/lookup_name_320035-2.json"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=Poa Annua",
+      include_str!("../../fixtures/ipni/search_poa_annua.json"),
+    )
+}
+
+fn entry(body: &str) -> Entry {
+  Entry {
+    stored_at: SystemTime::now(),
+    response: HttpResponse::new(StatusCode::OK, body),
+  }
+}
+
+fn body(lru: &mut Lru, key: &str) -> Option<String> {
+  lru
+    .get(key)
+    .map(|entry| String::from_utf8(entry.response.body).unwrap())
+}
+
+#[test]
+fn lru_evicts_oldest() {
+  let mut lru = Lru::new(2);
+  lru.put("a".into(), entry("a"));
+  lru.put("b".into(), entry("b"));
+  lru.put("c".into(), entry("c"));
+
+  assert_eq!(body(&mut lru, "a"), None);
+  assert_eq!(body(&mut lru, "b").as_deref(), Some("b"));
+  assert_eq!(body(&mut lru, "c").as_deref(), Some("c"));
+}
+
+#[test]
+fn lru_get_refreshes() {
+  let mut lru = Lru::new(2);
+  lru.put("a".into(), entry("a"));
+  lru.put("b".into(), entry("b"));
+  lru.get("a");
+  lru.put("c".into(), entry("c"));
+
+  assert_eq!(body(&mut lru, "a").as_deref(), Some("a"));
+  assert_eq!(body(&mut lru, "b"), None);
+}
+
+#[test]
+fn lru_put_replaces() {
+  let mut lru = Lru::new(2);
+  lru.put("a".into(), entry("a"));
+  lru.put("b".into(), entry("b"));
+  lru.put("a".into(), entry("a2"));
+
+  assert_eq!(lru.entries.len(), 2);
+  assert_eq!(body(&mut lru, "a").as_deref(), Some("a2"));
+  assert_eq!(body(&mut lru, "b").as_deref(), Some("b"));
+
+  let mut none = Lru::new(0);
+  none.put("a".into(), entry("a"));
+
+  assert_eq!(body(&mut none, "a"), None);
+}
+
+#[tokio::test]
+async fn cache_lookups() {
+  let client = KewClient::builder()
+    .transport(transport())
+    .cache(Cache::new().ttl(Endpoint::Search, Duration::ZERO))
+    .build()
+    .unwrap();
+  let id = "12653-1".parse().unwrap();
+
+  client.ipni().lookup_author(&id).await.unwrap();
+  client.ipni().lookup_author(&id).await.unwrap();
+
+  let cache = client.cache().unwrap();
+
+  assert_eq!(
+    cache.stats(),
+    CacheStats {
+      hits: 1,
+      misses: 1,
+      write_errors: 0
+    }
+  );
+
+  let url = "https://beta.ipni.org/api/1/a/12653-1".parse().unwrap();
+  cache.invalidate(&url).unwrap();
+  client.ipni().lookup_author(&id).await.unwrap();
+
+  assert_eq!(
+    cache.stats(),
+    CacheStats {
+      hits: 1,
+      misses: 2,
+      write_errors: 0
+    }
+  );
+
+  // Searches expire immediately.
+  client.ipni().search("Poa Annua".into()).await.unwrap();
+  client.ipni().search("Poa Annua".into()).await.unwrap();
+
+  assert_eq!(
+    cache.stats(),
+    CacheStats {
+      hits: 1,
+      misses: 4,
+      write_errors: 0
+    }
+  );
+}
+
+#[tokio::test]
+async fn cache_on_disk() {
+  let dir = std::env::temp_dir().join(format!("rustkew-cache-{}", std::process::id()));
+  let id = "12653-1".parse().unwrap();
+
+  let client = KewClient::builder()
+    .transport(transport())
+    .cache(Cache::new().disk(&dir))
+    .build()
+    .unwrap();
+  client.ipni().lookup_author(&id).await.unwrap();
+
+  // Nothing to fetch from, so the lookup has to come from disk.
+  let offline = KewClient::builder()
+    .transport(MemoryTransport::new())
+    .cache(Cache::new().memory(0).disk(&dir))
+    .build()
+    .unwrap();
+  let res = offline.ipni().lookup_author(&id).await.unwrap();
+
+  assert_eq!(res.standard_form, "L.");
+  assert_eq!(offline.cache().unwrap().stats().hits, 1);
+
+  offline.cache().unwrap().clear().unwrap();
+
+  assert!(matches!(
+    offline.ipni().lookup_author(&id).await,
+    Err(Error::Transport(_))
+  ));
+
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn cache_write_errors() {
+  // A file where the cache directory should be, so nothing can be written.
+  let path = std::env::temp_dir().join(format!("rustkew-not-a-dir-{}", std::process::id()));
+  std::fs::write(&path, "").unwrap();
+
+  let client = KewClient::builder()
+    .transport(transport())
+    .cache(Cache::new().disk(&path))
+    .build()
+    .unwrap();
+  let id = "12653-1".parse().unwrap();
+
+  client.ipni().lookup_author(&id).await.unwrap();
+  client.ipni().lookup_author(&id).await.unwrap();
+
+  assert_eq!(
+    client.cache().unwrap().stats(),
+    CacheStats {
+      hits: 1,
+      misses: 1,
+      write_errors: 1
+    }
+  );
+
+  std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn offline_mode() {
+  let dir = std::env::temp_dir().join(format!("rustkew-offline-{}", std::process::id()));
+  let id = "12653-1".parse().unwrap();
+
+  let client = KewClient::builder()
+    .transport(transport())
+    .cache(Cache::new().disk(&dir))
+    .build()
+    .unwrap();
+  client.ipni().lookup_author(&id).await.unwrap();
+
+  // Expired entries are still served offline, and the transport is never
+  // used even though it could answer.
+  let offline = KewClient::builder()
+    .transport(transport())
+    .cache(
+      Cache::new()
+        .disk(&dir)
+        .ttl(Endpoint::Lookup, Duration::ZERO),
+    )
+    .offline(true)
+    .build()
+    .unwrap();
+  let res = offline.ipni().lookup_author(&id).await.unwrap();
+
+  assert_eq!(res.standard_form, "L.");
+
+  let err = offline
+    .ipni()
+    .lookup_name(&"320035-2".parse().unwrap())
+    .await
+    .unwrap_err();
+
+  assert!(matches!(
+    err,
+    Error::OfflineMiss { url } if url.as_str() == "https://beta.ipni.org/api/1/n/320035-2"
+  ));
+
+  std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,342 @@
+use std::{
+  sync::{Arc, OnceLock},
+  time::Duration,
+};
+
+use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{
+  Api,
+  Cache,
+  Error,
+  ExtraFields,
+  RateLimit,
+  RetryPolicy,
+  UnknownFields,
+  core::{IPNI_URL, POWO_URL},
+  extra::Hook,
+  flight::Flights,
+  ipni::Ipni,
+  powo::Powo,
+  rate_limit::Limiter,
+  transport::{ReqwestTransport, Transport},
+};
+
+const DEFAULT_USER_AGENT: &str = concat!("rustkew/", env!("CARGO_PKG_VERSION"));
+
+static SHARED: OnceLock<KewClient> = OnceLock::new();
+
+/// The Kew service a request is sent to.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Deserialize, Serialize)]
+pub enum Service {
+  Ipni,
+  Powo,
+}
+
+/// A handle to the IPNI and POWO APIs.
+///
+/// Owns a single transport (by default a pooled `reqwest::Client`) along with
+/// the base URLs and request defaults, and is cheap to clone.
+#[derive(Debug, Clone)]
+pub struct KewClient {
+  inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+  transport: Arc<dyn Transport>,
+  headers: HeaderMap,
+  ipni_url: Url,
+  powo_url: Url,
+  retry: RetryPolicy,
+  limiter: Option<Limiter>,
+  flights: Flights,
+  cache: Option<Arc<Cache>>,
+  offline: bool,
+  strict: bool,
+  on_unknown_fields: Option<Hook>,
+}
+
+impl KewClient {
+  pub fn builder() -> KewClientBuilder {
+    KewClientBuilder::default()
+  }
+
+  /// The process-wide client used by `Api::new`, built from the defaults on
+  /// first use.
+  pub fn shared() -> KewClient {
+    SHARED
+      .get_or_init(|| {
+        KewClient::builder()
+          .build()
+          .expect("failed to build the default KewClient")
+      })
+      .clone()
+  }
+
+  pub fn ipni(&self) -> Ipni {
+    Ipni::with_client(self.clone())
+  }
+
+  pub fn powo(&self) -> Powo {
+    Powo::with_client(self.clone())
+  }
+
+  pub fn base_url(&self, service: Service) -> &Url {
+    match service {
+      Service::Ipni => &self.inner.ipni_url,
+      Service::Powo => &self.inner.powo_url,
+    }
+  }
+
+  pub(crate) fn transport(&self) -> &dyn Transport {
+    &*self.inner.transport
+  }
+
+  pub(crate) fn headers(&self) -> &HeaderMap {
+    &self.inner.headers
+  }
+
+  pub fn is_offline(&self) -> bool {
+    self.inner.offline
+  }
+
+  pub(crate) fn retry(&self) -> &RetryPolicy {
+    &self.inner.retry
+  }
+
+  pub(crate) fn limiter(&self) -> Option<&Limiter> {
+    self.inner.limiter.as_ref()
+  }
+
+  pub(crate) fn flights(&self) -> &Flights {
+    &self.inner.flights
+  }
+
+  /// The cache set with `KewClientBuilder::cache`, for its stats and to
+  /// invalidate entries.
+  pub fn cache(&self) -> Option<&Cache> {
+    self.inner.cache.as_deref()
+  }
+
+  /// Reports any unknown fields in a decoded response, failing instead if
+  /// the client is strict.
+  pub(crate) fn check_unknown(
+    &self,
+    service: Service,
+    method: &str,
+    record: &impl ExtraFields,
+  ) -> Result<(), Error> {
+    let fields = record.unknown_fields();
+    if fields.is_empty() {
+      return Ok(());
+    }
+
+    let unknown = UnknownFields {
+      service,
+      method: method.to_owned(),
+      fields,
+    };
+    if self.inner.strict {
+      return Err(Error::UnknownFields(unknown));
+    }
+    if let Some(Hook(hook)) = &self.inner.on_unknown_fields {
+      hook(&unknown);
+    }
+
+    Ok(())
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct KewClientBuilder {
+  transport: Option<Arc<dyn Transport>>,
+  ipni_url: String,
+  powo_url: String,
+  user_agent: String,
+  headers: HeaderMap,
+  timeout: Option<Duration>,
+  connect_timeout: Option<Duration>,
+  retry: RetryPolicy,
+  rate_limit: Option<RateLimit>,
+  cache: Option<Arc<Cache>>,
+  offline: bool,
+  strict: bool,
+  on_unknown_fields: Option<Hook>,
+}
+
+impl Default for KewClientBuilder {
+  fn default() -> Self {
+    Self {
+      transport: None,
+      ipni_url: IPNI_URL.into(),
+      powo_url: POWO_URL.into(),
+      user_agent: DEFAULT_USER_AGENT.into(),
+      headers: HeaderMap::new(),
+      timeout: None,
+      connect_timeout: None,
+      retry: RetryPolicy::default(),
+      rate_limit: None,
+      cache: None,
+      offline: false,
+      strict: false,
+      on_unknown_fields: None,
+    }
+  }
+}
+
+impl KewClientBuilder {
+  pub fn ipni_url(mut self, url: impl Into<String>) -> Self {
+    self.ipni_url = url.into();
+
+    self
+  }
+
+  pub fn powo_url(mut self, url: impl Into<String>) -> Self {
+    self.powo_url = url.into();
+
+    self
+  }
+
+  pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+    self.user_agent = user_agent.into();
+
+    self
+  }
+
+  /// Headers sent with every request, in addition to the user agent.
+  pub fn default_headers(mut self, headers: HeaderMap) -> Self {
+    self.headers.extend(headers);
+
+    self
+  }
+
+  /// Sends requests through `transport` instead of the network. The timeouts
+  /// only apply to the default transport.
+  pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+    self.transport = Some(Arc::new(transport));
+
+    self
+  }
+
+  /// Total time allowed for each request, including reading the body.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+
+    self
+  }
+
+  pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+    self.connect_timeout = Some(timeout);
+
+    self
+  }
+
+  /// How rate limited (429) and unavailable (502, 503, 504) responses are
+  /// retried. Use `RetryPolicy::none()` to fail on the first one.
+  pub fn retry(mut self, retry: RetryPolicy) -> Self {
+    self.retry = retry;
+
+    self
+  }
+
+  /// Throttles requests to each host before sending them, rather than only
+  /// backing off once the server responds with 429. `build` fails with
+  /// `Error::InvalidRateLimit` unless `per_second` is positive and finite.
+  pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+    self.rate_limit = Some(limit);
+
+    self
+  }
+
+  /// Keeps successful responses in `cache` and serves repeated requests
+  /// from it while they are fresh.
+  pub fn cache(mut self, cache: Cache) -> Self {
+    self.cache = Some(Arc::new(cache));
+
+    self
+  }
+
+  /// Answers requests only from the cache, however old the cached response,
+  /// and fails with `Error::OfflineMiss` for anything not in it rather than
+  /// touching the network. Pair with a `Cache::disk` store filled while
+  /// online.
+  pub fn offline(mut self, offline: bool) -> Self {
+    self.offline = offline;
+
+    self
+  }
+
+  /// Fails with `Error::UnknownFields` when a response has fields this crate
+  /// doesn't know about, rather than keeping them in the record's `extra`
+  /// map. Useful in tests to notice when the API has changed.
+  pub fn strict(mut self, strict: bool) -> Self {
+    self.strict = strict;
+
+    self
+  }
+
+  /// Called with the unknown fields in a response whenever there are any.
+  /// Not called in strict mode, where they are returned as an error instead.
+  pub fn on_unknown_fields(
+    mut self,
+    hook: impl Fn(&UnknownFields) + Send + Sync + 'static,
+  ) -> Self {
+    self.on_unknown_fields = Some(Hook(Arc::new(hook)));
+
+    self
+  }
+
+  pub fn build(self) -> Result<KewClient, Error> {
+    if let Some(limit) = &self.rate_limit
+      && !(limit.per_second.is_finite() && limit.per_second > 0.0)
+    {
+      return Err(Error::InvalidRateLimit {
+        per_second: limit.per_second,
+      });
+    }
+
+    let mut headers = self.headers;
+    headers.insert(
+      USER_AGENT,
+      HeaderValue::from_str(&self.user_agent).map_err(|_| Error::UserAgent(self.user_agent))?,
+    );
+
+    let transport = match self.transport {
+      Some(transport) => transport,
+      None => {
+        let mut http = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+          http = http.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+          http = http.connect_timeout(timeout);
+        }
+
+        Arc::new(ReqwestTransport::new(http.build()?))
+      },
+    };
+
+    Ok(KewClient {
+      inner: Arc::new(Inner {
+        transport,
+        headers,
+        ipni_url: parse_base_url(&self.ipni_url)?,
+        powo_url: parse_base_url(&self.powo_url)?,
+        retry: self.retry,
+        limiter: self.rate_limit.map(Limiter::new),
+        flights: Flights::default(),
+        cache: self.cache,
+        offline: self.offline,
+        strict: self.strict,
+        on_unknown_fields: self.on_unknown_fields,
+      }),
+    })
+  }
+}
+
+fn parse_base_url(url: &str) -> Result<Url, Error> {
+  Ok(Url::parse(url.trim_end_matches('/'))?)
+}
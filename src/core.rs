@@ -1,18 +1,33 @@
-use std::time::Duration;
-
-use reqwest::StatusCode;
+use reqwest::{Method, StatusCode};
 use serde::de::DeserializeOwned;
+use url::Url;
+
+use crate::{
+  Endpoint,
+  Error,
+  ExtraFields,
+  KewClient,
+  SearchResponse,
+  Service,
+  flight::Flight,
+  query::{Query, ToKey},
+  retry::retry_after,
+  transport::{HttpRequest, HttpResponse, url_key},
+};
 
 pub(crate) const IPNI_URL: &str = "https://beta.ipni.org/api/1";
 pub(crate) const POWO_URL: &str = "https://powo.science.kew.org/api/2";
 
+pub(crate) const DEFAULT_PER_PAGE: usize = 500;
+
 pub(crate) fn build_params<K: ToKey>(
   query: &Option<SearchQuery<K>>,
   filters: &Option<Vec<String>>,
   cursor: &str,
+  per_page: usize,
 ) -> impl Iterator<Item = (String, String)> {
   let mut params = vec![];
-  params.push((String::from("perPage"), String::from("500")));
+  params.push((String::from("perPage"), per_page.to_string()));
   params.push((String::from("cursor"), cursor.to_string()));
 
   if let Some(query) = query {
@@ -26,46 +41,194 @@ pub(crate) fn build_params<K: ToKey>(
   params.into_iter()
 }
 
-pub(crate) async fn get<R: DeserializeOwned>(
-  base_url: &'static str,
+/// The cursor for the page after `page`, or `None` if `page` was the last.
+///
+/// `*` is both the default cursor and the server's way of saying there is no
+/// next page, so it never continues a walk.
+pub(crate) fn next_cursor<R: Clone>(
+  current: Option<&str>,
+  page: &SearchResponse<R>,
+) -> Option<String> {
+  let next = page.cursor();
+
+  if page.results.is_empty() || next == "*" || Some(next) == current {
+    None
+  } else {
+    Some(next.to_owned())
+  }
+}
+
+pub(crate) async fn get<R: DeserializeOwned + ExtraFields + Clone + Send + Sync + 'static>(
+  client: &KewClient,
+  service: Service,
   method: impl Into<String>,
   params: impl Iterator<Item = (String, String)>,
-) -> Result<R, crate::Error> {
-  let url = format!("{}/{}", base_url, method.into());
-  let url = reqwest::Url::parse_with_params(&url, params)?;
+) -> Result<R, Error> {
+  let method = method.into();
+  let base_url = client.base_url(service).as_str().trim_end_matches('/');
+  let url = format!("{}/{}", base_url, method);
+  let mut url = reqwest::Url::parse_with_params(&url, params)?;
+  if url.query() == Some("") {
+    url.set_query(None);
+  }
+
+  // Offline, anything cached is better than nothing, however stale.
+  let endpoint = (!client.is_offline()).then(|| Endpoint::of(&method));
+  if let Some(res) = client.cache().and_then(|cache| cache.get(endpoint, &url)) {
+    let record = decode(&res.body)?;
+    client.check_unknown(service, &method, &record)?;
+
+    return Ok(record);
+  }
+  if client.is_offline() {
+    return Err(Error::OfflineMiss { url });
+  }
+
+  // Identical requests already in flight are shared. If that one fails, this
+  // one is sent on its own so it gets an error of its own.
+  let leader = match client.flights().join(url_key(&url)) {
+    Flight::Leader(leader) => Some(leader),
+    Flight::Follower(waiter) => {
+      if let Ok(record) = waiter.await
+        && let Some(record) = record.downcast_ref::<R>()
+      {
+        return Ok(record.clone());
+      }
+
+      None
+    },
+  };
+
+  let res = fetch(client, &method, &url).await?;
+  let record: R = decode(&res.body)?;
+  client.check_unknown(service, &method, &record)?;
+
+  // Only responses that decoded are cached, so a bad one is fetched again.
+  if let Some(cache) = client.cache() {
+    cache.put(&url, &res);
+  }
+  if let Some(leader) = leader {
+    leader.finish(|| record.clone());
+  }
+
+  Ok(record)
+}
+
+/// Sends a request, retrying as the client's `RetryPolicy` allows, and turns
+/// unsuccessful responses into errors.
+async fn fetch(client: &KewClient, method: &str, url: &Url) -> Result<HttpResponse, Error> {
+  let retry = client.retry();
+  let mut attempt = 0;
 
   let res = loop {
-    match reqwest::get(url.clone()).await {
-      Ok(res) => break res,
-      Err(err) if err.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {
-        tokio::time::sleep(Duration::from_millis(500)).await;
-        continue;
-      },
-      Err(err) => Err(err)?,
+    let req = HttpRequest {
+      method: Method::GET,
+      url: url.clone(),
+      headers: client.headers().clone(),
+    };
+    if let Some(limiter) = client.limiter() {
+      limiter.acquire(url).await;
+    }
+    let res = client.transport().send(req).await?;
+
+    if retry.should_retry(res.status, attempt)
+      && let Some(delay) = retry.delay(attempt, &res.headers)
+    {
+      tokio::time::sleep(delay).await;
+      attempt += 1;
+      continue;
     }
+
+    break res;
   };
 
-  Ok(res.json().await?)
+  let status = res.status;
+  if !status.is_success() {
+    let retry_after = retry_after(&res.headers);
+    let body = String::from_utf8_lossy(&res.body).into_owned();
+
+    return Err(match (status, record_id(method)) {
+      (StatusCode::NOT_FOUND, Some(id)) => Error::NotFound { id: id.to_owned() },
+      (StatusCode::TOO_MANY_REQUESTS, _) => Error::RateLimited { retry_after },
+      (status, _) => Error::Http { status, body },
+    });
+  }
+
+  Ok(res)
 }
 
-pub trait ToKey: Sized {
-  fn to_key(&self) -> &'static str;
+/// The id a record lookup such as `taxon/<id>` or `n/<id>` asks for, or
+/// `None` for other endpoints, where a 404 doesn't mean a missing record.
+fn record_id(method: &str) -> Option<&str> {
+  let (kind, id) = method.split_once('/')?;
+
+  matches!(kind, "taxon" | "n" | "a" | "p").then_some(id)
+}
+
+/// Deserializes a response body, reporting the JSON path of the field that
+/// failed along with the surrounding text.
+pub(crate) fn decode<R: DeserializeOwned>(body: &[u8]) -> Result<R, Error> {
+  let de = &mut serde_json::Deserializer::from_slice(body);
+
+  serde_path_to_error::deserialize(de).map_err(|err| {
+    let path = err.path().to_string();
+    let source = err.into_inner();
+
+    Error::Decode {
+      path,
+      raw_snippet: snippet(body, source.line(), source.column()),
+      source,
+    }
+  })
+}
+
+fn snippet(body: &[u8], line: usize, column: usize) -> String {
+  const CONTEXT: usize = 60;
+
+  let offset = body
+    .split(|b| *b == b'\n')
+    .take(line.saturating_sub(1))
+    .map(|l| l.len() + 1)
+    .sum::<usize>()
+    + column;
+  let offset = offset.min(body.len());
+
+  let start = offset.saturating_sub(CONTEXT);
+  let end = (offset + CONTEXT).min(body.len());
+
+  String::from_utf8_lossy(&body[start..end]).into_owned()
 }
 
 pub enum SearchQuery<K: ToKey> {
   String(String),
-  Vec(Vec<(K, String)>),
+  /// Terms added with `Api::query`, and an expression added with `Api::expr`.
+  Terms(Vec<(K, String)>, Option<Query<K>>),
 }
 
 impl<K: ToKey> SearchQuery<K> {
+  /// Renders the query for the `q` parameter. Terms are joined with commas,
+  /// the implicit AND the Kew websites use, and their values are sent as
+  /// given. An expression is added as one more clause, so adding one doesn't
+  /// change how the terms are sent.
   pub fn format(&self) -> String {
     match self {
       SearchQuery::String(s) => s.clone(),
-      SearchQuery::Vec(vec) => vec
-        .iter()
-        .map(|(k, v)| format!("{}:{}", k.to_key(), v))
-        .collect::<Vec<_>>()
-        .join(","),
+      SearchQuery::Terms(terms, expr) => {
+        let mut clauses = terms
+          .iter()
+          .map(|(k, v)| format!("{}:{}", k.to_key(), v))
+          .collect::<Vec<_>>();
+
+        if let Some(expr) = expr {
+          clauses.push(if expr.is_compound() && !clauses.is_empty() {
+            format!("({})", expr)
+          } else {
+            expr.to_string()
+          });
+        }
+
+        clauses.join(",")
+      },
     }
   }
 }
@@ -0,0 +1,102 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use serde_json::{Map, Value};
+
+use crate::{SearchResponse, Service};
+
+/// Records that keep fields this crate doesn't know about in an `extra` map
+/// instead of failing to decode, so new fields on the server don't break
+/// existing code.
+pub trait ExtraFields {
+  /// Appends the JSON path of every unknown field in this record, and in the
+  /// records nested in it, to `out`. `path` is the path of this record.
+  fn collect_unknown(&self, _path: &str, _out: &mut Vec<String>) {}
+
+  fn unknown_fields(&self) -> Vec<String> {
+    let mut out = vec![];
+    self.collect_unknown("", &mut out);
+
+    out
+  }
+}
+
+/// Unknown fields found in a response, as passed to the hook set with
+/// `KewClientBuilder::on_unknown_fields`.
+#[derive(Debug, Clone)]
+pub struct UnknownFields {
+  pub service: Service,
+  /// The endpoint, e.g. `search` or `taxon/urn:lsid:ipni.org:names:320035-2`.
+  pub method: String,
+  pub fields: Vec<String>,
+}
+
+impl fmt::Display for UnknownFields {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{:?} {} returned unknown fields: {}",
+      self.service,
+      self.method,
+      self.fields.join(", ")
+    )
+  }
+}
+
+#[derive(Clone)]
+pub(crate) struct Hook(pub(crate) Arc<dyn Fn(&UnknownFields) + Send + Sync>);
+
+impl fmt::Debug for Hook {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Hook")
+  }
+}
+
+/// Joins a field onto a path, e.g. `results[0]` and `synonymOf`.
+pub(crate) fn join(path: &str, field: &str) -> String {
+  if path.is_empty() {
+    field.to_owned()
+  } else {
+    format!("{}.{}", path, field)
+  }
+}
+
+/// Reports the keys of a record's `extra` map.
+pub(crate) fn collect_extra(extra: &Map<String, Value>, path: &str, out: &mut Vec<String>) {
+  out.extend(extra.keys().map(|key| join(path, key)));
+}
+
+impl<T: ExtraFields> ExtraFields for Option<T> {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    if let Some(value) = self {
+      value.collect_unknown(path, out);
+    }
+  }
+}
+
+impl<T: ExtraFields> ExtraFields for Box<T> {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    (**self).collect_unknown(path, out);
+  }
+}
+
+impl<T: ExtraFields> ExtraFields for Vec<T> {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    for (i, value) in self.iter().enumerate() {
+      value.collect_unknown(&format!("{}[{}]", path, i), out);
+    }
+  }
+}
+
+impl<K: fmt::Display, T: ExtraFields> ExtraFields for HashMap<K, T> {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    for (key, value) in self {
+      value.collect_unknown(&join(path, &key.to_string()), out);
+    }
+  }
+}
+
+impl<R: ExtraFields + Clone> ExtraFields for SearchResponse<R> {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    self.results.collect_unknown(&join(path, "results"), out);
+  }
+}
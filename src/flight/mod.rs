@@ -0,0 +1,85 @@
+use std::{
+  any::Any,
+  collections::HashMap,
+  fmt,
+  sync::{Arc, Mutex},
+};
+
+use futures::{FutureExt, channel::oneshot, future::Shared};
+
+#[cfg(test)]
+mod test;
+
+type Record = Arc<dyn Any + Send + Sync>;
+type Waiter = Shared<oneshot::Receiver<Record>>;
+
+/// Requests in flight, so that identical requests made at the same time share
+/// one network call.
+#[derive(Default)]
+pub(crate) struct Flights {
+  inflight: Mutex<HashMap<String, Waiter>>,
+}
+
+pub(crate) enum Flight<'a> {
+  /// The first request for a key, which makes the call.
+  Leader(Leader<'a>),
+  /// A later request, which waits for the leader's record. Resolves to an
+  /// error if the leader failed or was dropped, in which case the request
+  /// has to be made separately.
+  Follower(Waiter),
+}
+
+impl Flights {
+  pub(crate) fn join(&self, key: String) -> Flight<'_> {
+    let mut inflight = self.inflight.lock().unwrap();
+    if let Some(waiter) = inflight.get(&key) {
+      return Flight::Follower(waiter.clone());
+    }
+
+    let (tx, rx) = oneshot::channel();
+    inflight.insert(key.clone(), rx.shared());
+
+    Flight::Leader(Leader {
+      flights: self,
+      key,
+      tx: Some(tx),
+    })
+  }
+}
+
+impl fmt::Debug for Flights {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let inflight = self.inflight.lock().unwrap();
+    f.debug_set().entries(inflight.keys()).finish()
+  }
+}
+
+/// Removes its key when dropped, whether or not a record was handed out, so a
+/// failed or cancelled request doesn't block later ones.
+pub(crate) struct Leader<'a> {
+  flights: &'a Flights,
+  key: String,
+  tx: Option<oneshot::Sender<Record>>,
+}
+
+impl Leader<'_> {
+  /// Hands `record` to any followers. It is only built if there are some.
+  pub(crate) fn finish<R: Send + Sync + 'static>(mut self, record: impl FnOnce() -> R) {
+    let waiter = self.flights.inflight.lock().unwrap().remove(&self.key);
+
+    // The map held one handle, so any more belong to followers.
+    if let (Some(waiter), Some(tx)) = (waiter, self.tx.take())
+      && waiter.strong_count().is_some_and(|count| count > 1)
+    {
+      let _ = tx.send(Arc::new(record()));
+    }
+  }
+}
+
+impl Drop for Leader<'_> {
+  fn drop(&mut self) {
+    if self.tx.is_some() {
+      self.flights.inflight.lock().unwrap().remove(&self.key);
+    }
+  }
+}
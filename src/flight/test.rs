@@ -0,0 +1,73 @@
+use std::{
+  sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+  },
+  time::Duration,
+};
+
+use futures::future::BoxFuture;
+
+use crate::{
+  Error,
+  KewClient,
+  transport::{HttpRequest, HttpResponse, MemoryTransport, Transport},
+};
+
+/// Counts requests and holds each one up long enough for others to overlap.
+#[derive(Debug)]
+struct SlowTransport {
+  inner: MemoryTransport,
+  sent: Arc<AtomicUsize>,
+}
+
+impl Transport for SlowTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    self.sent.fetch_add(1, Ordering::SeqCst);
+
+    Box::pin(async move {
+      tokio::time::sleep(Duration::from_millis(20)).await;
+      self.inner.send(request).await
+    })
+  }
+}
+
+#[tokio::test]
+async fn single_flight() {
+  let sent = Arc::new(AtomicUsize::new(0));
+  let client = KewClient::builder()
+    .transport(SlowTransport {
+      inner: MemoryTransport::new().json(
+        "/api/1/suggest?query=Poa ann",
+        include_str!("../../fixtures/ipni/suggest_poa_ann.json"),
+      ),
+      sent: sent.clone(),
+    })
+    .build()
+    .unwrap();
+  let ipni = client.ipni();
+
+  let (a, b, c) = futures::join!(
+    ipni.suggest("Poa ann".into()),
+    ipni.suggest("Poa ann".into()),
+    ipni.suggest("Poa ann".into()),
+  );
+
+  assert_eq!(sent.load(Ordering::SeqCst), 1);
+  for res in [a, b, c] {
+    assert_eq!(res.unwrap().suggested_terms.scientific_name[0], "Poa annua");
+  }
+
+  // A failed request isn't shared, so each caller gets its own error.
+  let id = "1-1".parse().unwrap();
+  let (a, b) = futures::join!(ipni.lookup_name(&id), ipni.lookup_name(&id));
+
+  assert_eq!(sent.load(Ordering::SeqCst), 3);
+  assert!(matches!(a, Err(Error::Transport(_))));
+  assert!(matches!(b, Err(Error::Transport(_))));
+
+  // Once the first has finished, the same request is sent again.
+  ipni.suggest("Poa ann".into()).await.unwrap();
+
+  assert_eq!(sent.load(Ordering::SeqCst), 4);
+}
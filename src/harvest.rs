@@ -0,0 +1,92 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Service};
+
+/// A checkpoint in a cursor walk, written after every page so that a harvest
+/// that dies part way through can pick up where it left off.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+pub struct HarvestState {
+  pub service: Service,
+  /// The rendered `q` parameter.
+  pub query: Option<String>,
+  /// The rendered `f` parameter.
+  pub filters: Option<String>,
+  /// The cursor for the next page to fetch.
+  pub cursor: String,
+  /// Results fetched so far, across every run of the harvest.
+  pub fetched: usize,
+  pub finished: bool,
+}
+
+/// One page of a harvest, along with the state to save once its results have
+/// been handled.
+#[derive(Debug, Clone)]
+pub struct HarvestPage<R> {
+  pub results: Vec<R>,
+  pub state: HarvestState,
+}
+
+impl HarvestState {
+  pub(crate) fn new(
+    service: Service,
+    query: Option<String>,
+    filters: Option<String>,
+    cursor: String,
+  ) -> Self {
+    Self {
+      service,
+      query,
+      filters,
+      cursor,
+      fetched: 0,
+      finished: false,
+    }
+  }
+
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+    let file = fs::read(path)?;
+    serde_json::from_slice(&file).map_err(Error::HarvestState)
+  }
+
+  /// Writes the state to `path`, going through a temporary file so that a
+  /// crash mid-write never leaves a truncated checkpoint behind.
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+    let path = path.as_ref();
+    let tmp = path.with_extension("tmp");
+    let file = serde_json::to_vec_pretty(self).map_err(Error::HarvestState)?;
+
+    fs::write(&tmp, file)?;
+    Ok(fs::rename(tmp, path)?)
+  }
+
+  /// Checks that this state was saved by a harvest of the same query.
+  pub(crate) fn validate(
+    &self,
+    service: Service,
+    query: &Option<String>,
+    filters: &Option<String>,
+  ) -> Result<(), Error> {
+    if self.service != service {
+      return Err(Error::HarvestMismatch(format!(
+        "service was {:?}, now {:?}",
+        self.service, service
+      )));
+    }
+    if &self.query != query {
+      return Err(Error::HarvestMismatch(format!(
+        "query was {:?}, now {:?}",
+        self.query, query
+      )));
+    }
+    if &self.filters != filters {
+      return Err(Error::HarvestMismatch(format!(
+        "filters were {:?}, now {:?}",
+        self.filters, filters
+      )));
+    }
+
+    Ok(())
+  }
+}
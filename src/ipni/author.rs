@@ -0,0 +1,114 @@
+use serde::{Deserialize, Deserializer};
+use urn::Urn;
+
+use super::AuthorId;
+
+/// An IPNI author record.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Author {
+  pub id: AuthorId,
+  pub fq_id: Option<Urn>,
+  pub url: Option<String>,
+
+  /// The abbreviation used when citing names, e.g. `L.`.
+  pub standard_form: String,
+  pub forename: Option<String>,
+  pub surname: Option<String>,
+  #[serde(default, deserialize_with = "semicolon_list")]
+  pub alternative_abbreviations: Vec<String>,
+  #[serde(default, deserialize_with = "semicolon_list")]
+  pub alternative_names: Vec<String>,
+
+  /// Dates as IPNI gives them, e.g. `1707-1778` or `fl. 1850`. See
+  /// [`Author::years`] for the parsed form.
+  pub dates: Option<String>,
+  pub dates_type_code: Option<String>,
+
+  pub example_of_name_published: Option<String>,
+  #[serde(default, deserialize_with = "comma_list")]
+  pub taxon_groups: Vec<String>,
+  #[serde(default, deserialize_with = "comma_list")]
+  pub iso_countries: Vec<String>,
+  pub comments: Option<String>,
+
+  #[serde(default)]
+  pub suppressed: bool,
+  pub version: Option<String>,
+}
+
+/// Years parsed from [`Author::dates`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AuthorDates {
+  /// Year of birth, or the first year of activity.
+  pub from: Option<i32>,
+  /// Year of death, or the last year of activity.
+  pub to: Option<i32>,
+  /// Whether these are dates of activity (`fl.`) rather than a lifespan.
+  pub flourished: bool,
+}
+
+impl Author {
+  /// Parses `dates`, returning `None` if there are none or no year could be
+  /// found in them.
+  pub fn years(&self) -> Option<AuthorDates> {
+    let dates = self.dates.as_deref()?.trim();
+    let flourished = dates.starts_with("fl.");
+    let (from, to) = if let Some(rest) = dates.strip_prefix("b.") {
+      (year(rest), None)
+    } else if let Some(rest) = dates.strip_prefix("d.") {
+      (None, year(rest))
+    } else {
+      match dates.split_once('-') {
+        Some((from, to)) => (year(from), year(to)),
+        None => (year(dates), None),
+      }
+    };
+
+    (from.is_some() || to.is_some()).then_some(AuthorDates {
+      from,
+      to,
+      flourished,
+    })
+  }
+}
+
+/// The first run of digits in `s`, ignoring qualifiers like `c.` or `?`.
+fn year(s: &str) -> Option<i32> {
+  let start = s.find(|c: char| c.is_ascii_digit())?;
+  let digits = &s[start..];
+  let end = digits
+    .find(|c: char| !c.is_ascii_digit())
+    .unwrap_or(digits.len());
+
+  digits[..end].parse().ok()
+}
+
+fn semicolon_list<'de, D>(de: D) -> Result<Vec<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  list(de, ';')
+}
+
+fn comma_list<'de, D>(de: D) -> Result<Vec<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  list(de, ',')
+}
+
+fn list<'de, D>(de: D, sep: char) -> Result<Vec<String>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  Ok(
+    Option::<String>::deserialize(de)?
+      .unwrap_or_default()
+      .split(sep)
+      .map(str::trim)
+      .filter(|s| !s.is_empty())
+      .map(String::from)
+      .collect(),
+  )
+}
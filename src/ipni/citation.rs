@@ -0,0 +1,116 @@
+use serde::{Deserialize, Deserializer};
+use urn::Urn;
+
+use super::{AuthorId, NameId, Publication, PublicationId};
+
+/// An IPNI name record.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Citation {
+  pub id: NameId,
+  pub fq_id: Option<Urn>,
+  pub name: String,
+  pub url: Option<String>,
+
+  pub authors: Option<String>,
+  #[serde(default)]
+  pub author_team: Vec<AuthorTeamMember>,
+  pub publishing_author: Option<String>,
+
+  pub rank: Option<String>,
+  pub family: Option<String>,
+  pub infrafamily: Option<String>,
+  pub genus: Option<String>,
+  pub infragenus: Option<String>,
+  pub species: Option<String>,
+  pub infraspecies: Option<String>,
+  #[serde(default)]
+  pub hybrid: bool,
+  #[serde(default)]
+  pub hybrid_genus: bool,
+
+  /// The abbreviated title of the publication, e.g. `Sp. Pl.`.
+  pub publication: Option<String>,
+  pub publication_id: Option<PublicationId>,
+  pub linked_publication: Option<Box<Publication>>,
+  /// The full reference, e.g. `Sp. Pl. 1: 68 (1753)`.
+  pub reference: Option<String>,
+  pub reference_collation: Option<String>,
+  pub collation: Option<String>,
+  pub publication_year: Option<i32>,
+  pub publication_year_note: Option<String>,
+  pub bhl_link: Option<String>,
+
+  pub citation_type: Option<String>,
+  pub name_status: Option<String>,
+  #[serde(default, deserialize_with = "name_link")]
+  pub basionym: Option<NameLink>,
+  #[serde(default, deserialize_with = "name_link")]
+  pub replaced_synonym: Option<NameLink>,
+  #[serde(default)]
+  pub in_powo: bool,
+
+  pub distribution: Option<String>,
+  pub type_locations: Option<String>,
+  pub type_remarks: Option<String>,
+  pub collector_team: Option<String>,
+  pub collection_number: Option<String>,
+  pub remarks: Option<String>,
+
+  #[serde(default)]
+  pub suppressed: bool,
+  #[serde(default)]
+  pub top_copy: bool,
+  pub version: Option<String>,
+  #[serde(alias = "dateCreated")]
+  pub added: Option<String>,
+  #[serde(alias = "lastModified", alias = "dateModified")]
+  pub modified: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct AuthorTeamMember {
+  pub id: AuthorId,
+  pub name: String,
+  /// `Aut` for authors, `Ex` for authors cited with "ex".
+  pub role: Option<String>,
+  pub summary: Option<String>,
+  pub url: Option<String>,
+}
+
+/// A reference from one name to another, such as a basionym.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct NameLink {
+  pub id: Option<NameId>,
+  pub fq_id: Option<Urn>,
+  pub name: String,
+  pub authors: Option<String>,
+  pub url: Option<String>,
+}
+
+/// Links are usually full records, but older ones are only the name as text.
+fn name_link<'de, D>(de: D) -> Result<Option<NameLink>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Link {
+    Record(NameLink),
+    Text(String),
+  }
+
+  Ok(match Option::<Link>::deserialize(de)? {
+    Some(Link::Record(link)) => Some(link),
+    Some(Link::Text(name)) => Some(NameLink {
+      id: None,
+      fq_id: None,
+      name,
+      authors: None,
+      url: None,
+    }),
+    None => None,
+  })
+}
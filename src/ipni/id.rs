@@ -0,0 +1,233 @@
+use std::{
+  fmt,
+  hash::{Hash, Hasher},
+  marker::PhantomData,
+  str::FromStr,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use urn::{Urn, UrnBuilder};
+
+/// The kind of record an `IpniId` refers to.
+pub trait IdKind {
+  /// The LSID namespace, e.g. `names` in `urn:lsid:ipni.org:names:320035-2`.
+  const NAMESPACE: &'static str;
+}
+
+/// Marker for name (citation) ids.
+#[derive(Debug)]
+pub enum NameRecord {}
+
+/// Marker for author ids.
+#[derive(Debug)]
+pub enum AuthorRecord {}
+
+/// Marker for publication ids.
+#[derive(Debug)]
+pub enum PublicationRecord {}
+
+impl IdKind for NameRecord {
+  const NAMESPACE: &'static str = "names";
+}
+
+impl IdKind for AuthorRecord {
+  const NAMESPACE: &'static str = "authors";
+}
+
+impl IdKind for PublicationRecord {
+  const NAMESPACE: &'static str = "publications";
+}
+
+pub type NameId = IpniId<NameRecord>;
+pub type AuthorId = IpniId<AuthorRecord>;
+pub type PublicationId = IpniId<PublicationRecord>;
+
+/// Why a string or URN isn't a valid IPNI id.
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum IdError {
+  #[error("invalid IPNI id {0:?}")]
+  Invalid(String),
+  #[error("expected an IPNI {expected} id, got {found:?}")]
+  WrongKind {
+    expected: &'static str,
+    found: String,
+  },
+}
+
+/// An IPNI record id such as `320035-2`, tagged with the kind of record it
+/// belongs to so a name id can't be passed to `Ipni::lookup_author`.
+///
+/// Parses from either the bare id or its LSID
+/// (`urn:lsid:ipni.org:names:320035-2`), and displays as the bare id.
+pub struct IpniId<K> {
+  id: String,
+  kind: PhantomData<fn() -> K>,
+}
+
+impl<K: IdKind> IpniId<K> {
+  pub fn as_str(&self) -> &str {
+    &self.id
+  }
+
+  /// The LSID for this record.
+  pub fn to_urn(&self) -> Urn {
+    UrnBuilder::new("lsid", &format!("ipni.org:{}:{}", K::NAMESPACE, self.id))
+      .build()
+      .expect("IPNI ids are valid URN components")
+  }
+}
+
+/// Ids are one or more digits, a hyphen, and a check digit.
+fn is_bare_id(id: &str) -> bool {
+  match id.split_once('-') {
+    Some((number, check)) => {
+      !number.is_empty()
+        && !check.is_empty()
+        && number.bytes().all(|b| b.is_ascii_digit())
+        && check.bytes().all(|b| b.is_ascii_digit())
+    },
+    None => false,
+  }
+}
+
+impl<K: IdKind> FromStr for IpniId<K> {
+  type Err = IdError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    const PREFIX: &str = "urn:lsid:ipni.org:";
+
+    let s = s.trim();
+    // The scheme, NID and authority are case-insensitive.
+    let rest = s
+      .get(..PREFIX.len())
+      .filter(|prefix| prefix.eq_ignore_ascii_case(PREFIX))
+      .map(|_| &s[PREFIX.len()..]);
+    let id = match rest {
+      Some(rest) => {
+        // The namespace, the id, and optionally a revision we don't need.
+        let mut parts = rest.split(':');
+        let namespace = parts.next().unwrap_or_default();
+        if namespace != K::NAMESPACE {
+          return Err(IdError::WrongKind {
+            expected: K::NAMESPACE,
+            found: s.to_owned(),
+          });
+        }
+
+        let id = parts.next().unwrap_or_default();
+        let revision = parts.next();
+        if revision.is_some_and(str::is_empty) || parts.next().is_some() {
+          return Err(IdError::Invalid(s.to_owned()));
+        }
+
+        id
+      },
+      None => s,
+    };
+
+    if !is_bare_id(id) {
+      return Err(IdError::Invalid(s.to_owned()));
+    }
+
+    Ok(Self {
+      id: id.to_owned(),
+      kind: PhantomData,
+    })
+  }
+}
+
+impl<K: IdKind> TryFrom<&Urn> for IpniId<K> {
+  type Error = IdError;
+
+  fn try_from(urn: &Urn) -> Result<Self, Self::Error> {
+    urn.as_str().parse()
+  }
+}
+
+impl<K: IdKind> TryFrom<Urn> for IpniId<K> {
+  type Error = IdError;
+
+  fn try_from(urn: Urn) -> Result<Self, Self::Error> {
+    (&urn).try_into()
+  }
+}
+
+impl<K: IdKind> From<IpniId<K>> for Urn {
+  fn from(id: IpniId<K>) -> Self {
+    id.to_urn()
+  }
+}
+
+impl<K: IdKind> From<&IpniId<K>> for Urn {
+  fn from(id: &IpniId<K>) -> Self {
+    id.to_urn()
+  }
+}
+
+impl<K> fmt::Display for IpniId<K> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.id)
+  }
+}
+
+impl<K: IdKind> fmt::Debug for IpniId<K> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "IpniId({}:{})", K::NAMESPACE, self.id)
+  }
+}
+
+// Implemented by hand so that `K` doesn't need to be `Clone`, `Eq` and so on.
+impl<K> Clone for IpniId<K> {
+  fn clone(&self) -> Self {
+    Self {
+      id: self.id.clone(),
+      kind: PhantomData,
+    }
+  }
+}
+
+impl<K> PartialEq for IpniId<K> {
+  fn eq(&self, other: &Self) -> bool {
+    self.id == other.id
+  }
+}
+
+impl<K> Eq for IpniId<K> {}
+
+impl<K> Hash for IpniId<K> {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.id.hash(state);
+  }
+}
+
+impl<K> PartialEq<str> for IpniId<K> {
+  fn eq(&self, other: &str) -> bool {
+    self.id == other
+  }
+}
+
+impl<K> PartialEq<&str> for IpniId<K> {
+  fn eq(&self, other: &&str) -> bool {
+    self.id == *other
+  }
+}
+
+impl<'de, K: IdKind> Deserialize<'de> for IpniId<K> {
+  fn deserialize<D>(de: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    String::deserialize(de)?
+      .parse()
+      .map_err(serde::de::Error::custom)
+  }
+}
+
+impl<K> Serialize for IpniId<K> {
+  fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    self.id.serialize(ser)
+  }
+}
@@ -1,15 +1,41 @@
+use futures::{Stream, StreamExt, TryStreamExt, stream};
 use serde::Deserialize;
 
 use crate::{
   Api,
+  Batch,
   Error,
+  ExtraFields,
+  HarvestPage,
+  HarvestState,
+  KewClient,
   SearchResponse,
-  core::{SearchQuery, build_params, get},
+  Service,
+  core::{DEFAULT_PER_PAGE, SearchQuery, build_params, get, next_cursor},
+  query::Query,
 };
 
+mod author;
+mod citation;
 mod filters;
+mod id;
+mod publication;
 mod suggest;
 mod terms;
+pub use author::{Author, AuthorDates};
+pub use citation::{AuthorTeamMember, Citation, NameLink};
+pub use id::{
+  AuthorId,
+  AuthorRecord,
+  IdError,
+  IdKind,
+  IpniId,
+  NameId,
+  NameRecord,
+  PublicationId,
+  PublicationRecord,
+};
+pub use publication::Publication;
 pub use suggest::{SuggestResult, SuggestedTerms, Suggestion, Suggestions};
 pub use terms::{IpniQuery, Name};
 
@@ -20,69 +46,78 @@ mod test;
 
 #[derive(Debug, Clone)]
 pub struct Ipni {
+  client: KewClient,
   query: Option<Vec<(IpniQuery, String)>>,
+  expr: Option<Query<IpniQuery>>,
   filters: Option<Vec<Filters>>,
   cursor: Option<String>,
+  per_page: Option<usize>,
+  limit: Option<usize>,
 }
 
 impl_api!(
   Ipni,
-  URL: crate::core::IPNI_URL,
+  Service: Service::Ipni,
   Filters: Filters,
   Ok: IpniResult,
   Query: IpniQuery
 );
 
 impl Ipni {
-  pub async fn search(query: String) -> Result<SearchResponse<<Self as Api>::Ok>, Error> {
-    let query = Some(SearchQuery::<<Self as Api>::Query>::String(query));
-    let params = build_params(
-      &query,
-      &None::<Vec<String>>,
-      "*",
-    );
-    get(Self::URL, "search", params).await
+  /// Runs a raw query string, using this builder's filters, cursor and page
+  /// options.
+  pub async fn search(&self, query: String) -> Result<SearchResponse<<Self as Api>::Ok>, Error> {
+    self.send_search(Some(SearchQuery::String(query))).await
   }
 
-  pub async fn lookup_name(id: String) -> Result<Citation, Error> {
-    get(Self::URL, format!("n/{}", id), [].into_iter()).await
+  pub async fn lookup_name(&self, id: &NameId) -> Result<Citation, Error> {
+    get(
+      &self.client,
+      Self::SERVICE,
+      format!("n/{}", id),
+      [].into_iter(),
+    )
+    .await
   }
 
-  pub async fn lookup_publication(id: String) -> Result<Publication, Error> {
-    get(Self::URL, format!("p/{}", id), [].into_iter()).await
-  }
+  /// Looks up many names concurrently. See `Batch` for the options.
+  pub fn lookup_names(&self, ids: impl IntoIterator<Item = NameId>) -> Batch<NameId, Citation> {
+    let ipni = self.clone();
 
-  pub async fn lookup_author(id: String) -> Result<Author, Error> {
-    get(Self::URL, format!("a/{}", id), [].into_iter()).await
+    Batch::new(ids, move |id| {
+      let ipni = ipni.clone();
+      Box::pin(async move { ipni.lookup_name(&id).await })
+    })
   }
 
-  pub async fn suggest(query: String) -> Result<SuggestResult, Error> {
-    suggest(query).await
+  pub async fn lookup_publication(&self, id: &PublicationId) -> Result<Publication, Error> {
+    get(
+      &self.client,
+      Self::SERVICE,
+      format!("p/{}", id),
+      [].into_iter(),
+    )
+    .await
   }
-}
 
-#[derive(Debug, Deserialize, Clone)]
-pub struct Citation {
-  pub name: String,
-  pub id: String,
-}
-
-#[derive(Debug, Deserialize, Clone)]
-pub struct Author {
-  pub id: String,
-  #[serde(rename(deserialize = "standardForm"))]
-  pub standard_form: String,
-}
+  pub async fn lookup_author(&self, id: &AuthorId) -> Result<Author, Error> {
+    get(
+      &self.client,
+      Self::SERVICE,
+      format!("a/{}", id),
+      [].into_iter(),
+    )
+    .await
+  }
 
-#[derive(Debug, Deserialize, Clone)]
-#[serde(tag = "recordType")]
-pub struct Publication {
-  pub id: String,
-  pub title: String,
+  pub async fn suggest(&self, query: String) -> Result<SuggestResult, Error> {
+    suggest(&self.client, query).await
+  }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 #[serde(tag = "recordType")]
+#[allow(clippy::large_enum_variant)]
 pub enum IpniResult {
   #[serde(rename(deserialize = "citation"))]
   Citation(Citation),
@@ -91,3 +126,11 @@ pub enum IpniResult {
   #[serde(rename(deserialize = "publication"))]
   Publication(Publication),
 }
+
+// IPNI records ignore fields they don't know about, so there is never
+// anything to report.
+impl ExtraFields for Citation {}
+impl ExtraFields for Author {}
+impl ExtraFields for Publication {}
+impl ExtraFields for IpniResult {}
+impl ExtraFields for SuggestResult {}
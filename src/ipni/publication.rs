@@ -0,0 +1,65 @@
+use serde::{Deserialize, Deserializer};
+use urn::Urn;
+
+use super::PublicationId;
+
+/// An IPNI publication record.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct Publication {
+  pub id: PublicationId,
+  pub fq_id: Option<Urn>,
+  pub url: Option<String>,
+
+  pub title: String,
+  /// The abbreviation used in citations, e.g. `Sp. Pl.`.
+  pub abbreviation: Option<String>,
+  /// Publication dates as IPNI gives them, e.g. `1753` or `1762-1763`.
+  pub date: Option<String>,
+  pub remarks: Option<String>,
+
+  /// Botanico-Periodicum-Huntianum number.
+  pub bph_number: Option<String>,
+  pub isbn: Option<String>,
+  pub issn: Option<String>,
+  /// Library of Congress call number.
+  pub lc_number: Option<String>,
+  /// Taxonomic Literature, 2nd edition.
+  pub tl2_author: Option<String>,
+  pub tl2_number: Option<String>,
+
+  /// The publication this one continues.
+  #[serde(default, deserialize_with = "publication_id")]
+  pub preceded_by: Option<PublicationId>,
+  /// The publication that continues this one.
+  #[serde(default, alias = "supercededBy", deserialize_with = "publication_id")]
+  pub superseded_by: Option<PublicationId>,
+
+  #[serde(default)]
+  pub suppressed: bool,
+  pub version: Option<String>,
+}
+
+/// Succession links come as either a linked record or an id.
+fn publication_id<'de, D>(de: D) -> Result<Option<PublicationId>, D::Error>
+where
+  D: Deserializer<'de>,
+{
+  #[derive(Deserialize)]
+  struct Record {
+    id: PublicationId,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Link {
+    Record(Record),
+    Id(PublicationId),
+  }
+
+  Ok(match Option::<Link>::deserialize(de)? {
+    Some(Link::Record(Record { id })) => Some(id),
+    Some(Link::Id(id)) => Some(id),
+    None => None,
+  })
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::core::get;
+use crate::{KewClient, Service, core::get};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 #[serde(rename_all(deserialize = "kebab-case"))]
@@ -33,9 +33,10 @@ pub struct SuggestResult {
   pub suggested_terms: SuggestedTerms,
 }
 
-pub async fn suggest(query: String) -> Result<SuggestResult, crate::Error> {
+pub async fn suggest(client: &KewClient, query: String) -> Result<SuggestResult, crate::Error> {
   get(
-    crate::core::IPNI_URL,
+    client,
+    Service::Ipni,
     "suggest",
     [("query".to_owned(), query)].into_iter(),
   )
@@ -8,6 +8,15 @@ pub enum Author {
   Surname,
 }
 
+impl Author {
+  pub const ALL: [Author; 4] = [
+    Author::Forename,
+    Author::FullName,
+    Author::StandardForm,
+    Author::Surname,
+  ];
+}
+
 impl From<Author> for &'static str {
   fn from(val: Author) -> Self {
     match val {
@@ -1,4 +1,6 @@
-use crate::core::ToKey;
+use std::str::FromStr;
+
+use crate::query::{ParseError, ToKey};
 mod author;
 mod name;
 mod publication;
@@ -27,4 +29,20 @@ impl ToKey for IpniQuery {
   fn to_key(&self) -> &'static str {
     (*self).into()
   }
+
+  fn keys() -> impl Iterator<Item = Self> {
+    Author::ALL
+      .into_iter()
+      .map(IpniQuery::from)
+      .chain(Name::ALL.into_iter().map(IpniQuery::from))
+      .chain(Publication::ALL.into_iter().map(IpniQuery::from))
+  }
+}
+
+impl FromStr for IpniQuery {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, ParseError> {
+    Self::from_key(s)
+  }
 }
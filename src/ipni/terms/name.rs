@@ -30,6 +30,37 @@ pub enum Name {
   Version,
 }
 
+impl Name {
+  pub const ALL: [Name; 26] = [
+    Name::Added,
+    Name::Author,
+    Name::Basionym,
+    Name::BasionymAuthor,
+    Name::BibliographicReference,
+    Name::CitationType,
+    Name::CollectionNumber,
+    Name::Collectors,
+    Name::Distribution,
+    Name::Family,
+    Name::FullName,
+    Name::Genus,
+    Name::InPowo,
+    Name::Infrafamily,
+    Name::Infragenus,
+    Name::Infraspecies,
+    Name::Modified,
+    Name::NameStatus,
+    Name::Published,
+    Name::PublishedIn,
+    Name::PublishingAuthor,
+    Name::Rank,
+    Name::ScientificName,
+    Name::Species,
+    Name::SpeciesAuthor,
+    Name::Version,
+  ];
+}
+
 impl From<Name> for &'static str {
   fn from(val: Name) -> Self {
     match val {
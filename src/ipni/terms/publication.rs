@@ -15,6 +15,22 @@ pub enum Publication {
   Tl2Number,
 }
 
+impl Publication {
+  pub const ALL: [Publication; 11] = [
+    Publication::StandardForm,
+    Publication::BphNumber,
+    Publication::Date,
+    Publication::Isbn,
+    Publication::Issn,
+    Publication::LcNumber,
+    Publication::PrecededBy,
+    Publication::SupercededBy,
+    Publication::Title,
+    Publication::Tl2Author,
+    Publication::Tl2Number,
+  ];
+}
+
 impl From<Publication> for &'static str {
   fn from(val: Publication) -> Self {
     match val {
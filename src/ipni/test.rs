@@ -1,9 +1,93 @@
-use super::{filters::Filters, terms, Author, Citation, Ipni, IpniResult, Publication};
-use crate::Api;
+use std::{str::FromStr, time::Duration};
+
+use reqwest::{StatusCode, header::RETRY_AFTER};
+use urn::Urn;
+
+use super::{
+  Author,
+  AuthorDates,
+  AuthorId,
+  Citation,
+  IdError,
+  IpniResult,
+  NameId,
+  Publication,
+  filters::Filters,
+  terms,
+};
+use crate::{
+  Api,
+  Error,
+  KewClient,
+  RetryPolicy,
+  transport::{HttpResponse, MemoryTransport},
+};
+
+macro_rules! fixture {
+  ($name:literal) => {
+    include_str!(concat!("../../fixtures/ipni/", $name, ".json"))
+  };
+}
+
+fn transport() -> MemoryTransport {
+  MemoryTransport::new()
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=Poa Annua",
+      fixture!("search_poa_annua"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=genus:Poa,species:annua,name author:L.",
+      fixture!("search_poa_annua_l"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=author std:L.",
+      fixture!("search_author_l"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=lc number:QK91.S6",
+      fixture!("search_lc_number"),
+    )
+    .json("/api/1/n/320035-2", fixture!("lookup_name_320035-2"))
+    .json("/api/1/p/1071-2", fixture!("lookup_publication_1071-2"))
+    .json("/api/1/a/12653-1", fixture!("lookup_author_12653-1"))
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae",
+      fixture!("search_family_poaceae"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae&f=f_familial",
+      fixture!("search_family_poaceae_familial"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae&f=f_infrafamilial",
+      fixture!("search_family_poaceae_infrafamilial"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae&f=f_generic",
+      fixture!("search_family_poaceae_generic"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae&f=f_infrageneric",
+      fixture!("search_family_poaceae_infrageneric"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae&f=f_specific",
+      fixture!("search_family_poaceae_specific"),
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=family:Poaceae&f=f_infraspecific",
+      fixture!("search_family_poaceae_infraspecific"),
+    )
+    .json("/api/1/suggest?query=Poa ann", fixture!("suggest_poa_ann"))
+}
+
+fn client() -> KewClient {
+  KewClient::builder().transport(transport()).build().unwrap()
+}
 
 #[tokio::test]
 async fn basic_search() {
-  let res = Ipni::search("Poa Annua".into()).await.unwrap();
+  let res = client().ipni().search("Poa Annua".into()).await.unwrap();
 
   assert_eq!(res.size(), 13);
 
@@ -18,7 +102,8 @@ async fn basic_search() {
 
 #[tokio::test]
 async fn advanced_name_search() {
-  let query = Ipni::new()
+  let query = client()
+    .ipni()
     .query(terms::Name::Genus, "Poa")
     .query(terms::Name::Species, "annua")
     .query(terms::Name::Author, "L.");
@@ -38,7 +123,8 @@ async fn advanced_name_search() {
 
 #[tokio::test]
 async fn advanced_author_search() {
-  let res = Ipni::new()
+  let res = client()
+    .ipni()
     .query(terms::Author::StandardForm, "L.")
     .run()
     .await
@@ -58,7 +144,8 @@ async fn advanced_author_search() {
 
 #[tokio::test]
 async fn advanced_publication_search() {
-  let res = Ipni::new()
+  let res = client()
+    .ipni()
     .query(terms::Publication::LcNumber, "QK91.S6")
     .run()
     .await
@@ -75,28 +162,226 @@ async fn advanced_publication_search() {
 
 #[tokio::test]
 async fn lookup_name() {
-  let res = Ipni::lookup_name("320035-2".into()).await.unwrap();
+  let res = client()
+    .ipni()
+    .lookup_name(&"320035-2".parse().unwrap())
+    .await
+    .unwrap();
 
   assert_eq!(res.name, "Poa annua");
+  assert_eq!(res.authors.as_deref(), Some("L."));
+  assert_eq!(res.rank.as_deref(), Some("spec."));
+  assert_eq!(res.family.as_deref(), Some("Poaceae"));
+  assert_eq!(res.genus.as_deref(), Some("Poa"));
+  assert_eq!(res.species.as_deref(), Some("annua"));
+  assert_eq!(res.infraspecies, None);
+  assert_eq!(res.reference.as_deref(), Some("Sp. Pl. 1: 68 (1753)"));
+  assert_eq!(res.publication_year, Some(1753));
+  assert_eq!(res.citation_type.as_deref(), Some("tax. nov."));
+  assert_eq!(res.author_team[0].id, "12653-1");
+  assert_eq!(res.linked_publication.unwrap().title, "Species Plantarum");
+  assert!(res.in_powo);
+  assert_eq!(res.version.as_deref(), Some("1.1"));
+}
+
+#[tokio::test]
+async fn citation_links() {
+  let res = client().ipni().search("Poa Annua".into()).await.unwrap();
+  let citations = res
+    .results()
+    .iter()
+    .filter_map(|res| match res {
+      IpniResult::Citation(citation) => Some(citation),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+
+  let exilis = citations.iter().find(|c| c.id == "387606-1").unwrap();
+  let basionym = exilis.basionym.as_ref().unwrap();
+
+  assert_eq!(basionym.id.as_ref().unwrap(), "387390-1");
+  assert_eq!(basionym.name, "Poa exilis");
+  assert_eq!(exilis.infraspecies.as_deref(), Some("exilis"));
+  assert_eq!(exilis.type_locations.as_deref(), Some("Italy, Trieste"));
+  assert_eq!(exilis.collector_team.as_deref(), Some("Tommasini"));
+
+  let reptans = citations.iter().find(|c| c.id == "387605-1").unwrap();
+
+  assert_eq!(reptans.name_status.as_deref(), Some("nom. illeg."));
+  assert_eq!(
+    reptans.replaced_synonym.as_ref().unwrap().name,
+    "Poa reptans Savi"
+  );
+  assert!(!reptans.in_powo);
 }
 
 #[tokio::test]
 async fn lookup_publication() {
-  let res = Ipni::lookup_publication("1071-2".into()).await.unwrap();
+  let res = client()
+    .ipni()
+    .lookup_publication(&"1071-2".parse().unwrap())
+    .await
+    .unwrap();
 
   assert_eq!(res.title, "Species Plantarum");
+  assert_eq!(res.abbreviation.as_deref(), Some("Sp. Pl."));
+  assert_eq!(res.date.as_deref(), Some("1753"));
+  assert_eq!(res.bph_number.as_deref(), Some("845.01"));
+  assert_eq!(res.lc_number.as_deref(), Some("QK91.S6"));
+  assert_eq!(res.tl2_number.as_deref(), Some("4.769"));
+  assert_eq!(res.preceded_by, None);
+  assert_eq!(res.superseded_by.as_ref().unwrap(), "1072-2");
+}
+
+#[test]
+fn parse_ids() {
+  let bare: NameId = "320035-2".parse().unwrap();
+  let lsid: NameId = "urn:lsid:ipni.org:names:320035-2".parse().unwrap();
+  let revision: NameId = "urn:lsid:ipni.org:names:320035-2:1.1".parse().unwrap();
+
+  assert_eq!(bare, lsid);
+  assert_eq!(bare, revision);
+  assert_eq!(bare.to_string(), "320035-2");
+  assert_eq!(
+    bare.to_urn().to_string(),
+    "urn:lsid:ipni.org:names:320035-2"
+  );
+
+  assert_eq!(
+    "urn:lsid:ipni.org:authors:12653-1".parse::<NameId>(),
+    Err(IdError::WrongKind {
+      expected: "names",
+      found: "urn:lsid:ipni.org:authors:12653-1".into(),
+    })
+  );
+  assert_eq!(
+    "Poa annua".parse::<NameId>(),
+    Err(IdError::Invalid("Poa annua".into()))
+  );
+  assert!("12653".parse::<AuthorId>().is_err());
+
+  let upper: NameId = "URN:LSID:IPNI.org:names:320035-2".parse().unwrap();
+
+  assert_eq!(upper, bare);
+  assert_eq!(
+    "urn:lsid:ipni.org:names:320035-2:foo:bar".parse::<NameId>(),
+    Err(IdError::Invalid(
+      "urn:lsid:ipni.org:names:320035-2:foo:bar".into()
+    ))
+  );
+  assert!(
+    "urn:lsid:ipni.org:names:320035-2:"
+      .parse::<NameId>()
+      .is_err()
+  );
+}
+
+#[tokio::test]
+async fn lookup_name_by_urn() {
+  // As found in `PowoResult::fq_id`.
+  let urn = Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap();
+  let id = NameId::try_from(&urn).unwrap();
+
+  let res = client().ipni().lookup_name(&id).await.unwrap();
+
+  assert_eq!(res.id, id);
+  assert_eq!(Urn::from(res.id), urn);
+}
+
+#[tokio::test]
+async fn publication_succession() {
+  let res = client()
+    .ipni()
+    .query(terms::Publication::LcNumber, "QK91.S6")
+    .run()
+    .await
+    .unwrap();
+
+  let second = res
+    .results()
+    .iter()
+    .find_map(|res| match res {
+      IpniResult::Publication(publication) if publication.id == "1072-2" => Some(publication),
+      _ => None,
+    })
+    .unwrap();
+
+  assert_eq!(second.isbn.as_deref(), Some("978-3-7682-0708-0"));
+  assert_eq!(second.issn, None);
+  assert_eq!(second.preceded_by.as_ref().unwrap(), "1071-2");
+  assert_eq!(second.superseded_by, None);
 }
 
 #[tokio::test]
 async fn lookup_author() {
-  let res = Ipni::lookup_author("12653-1".into()).await.unwrap();
+  let res = client()
+    .ipni()
+    .lookup_author(&"12653-1".parse().unwrap())
+    .await
+    .unwrap();
 
   assert_eq!(res.standard_form, "L.");
+  assert_eq!(res.forename.as_deref(), Some("Carl"));
+  assert_eq!(res.surname.as_deref(), Some("Linnaeus"));
+  assert_eq!(res.alternative_abbreviations, ["Linn.", "Linnaeus"]);
+  assert_eq!(
+    res.taxon_groups,
+    ["Bryophytes", "Pteridophytes", "Spermatophytes"]
+  );
+  assert_eq!(
+    res.example_of_name_published.as_deref(),
+    Some("Poa annua L.")
+  );
+  assert_eq!(
+    res.years(),
+    Some(AuthorDates {
+      from: Some(1707),
+      to: Some(1778),
+      flourished: false,
+    })
+  );
+}
+
+#[tokio::test]
+async fn author_dates() {
+  let res = client()
+    .ipni()
+    .query(terms::Author::StandardForm, "L.")
+    .run()
+    .await
+    .unwrap();
+  let authors = res
+    .results()
+    .iter()
+    .filter_map(|res| match res {
+      IpniResult::Author(author) => Some(author),
+      _ => None,
+    })
+    .collect::<Vec<_>>();
+
+  let son = authors.iter().find(|a| a.id == "12654-1").unwrap();
+
+  assert_eq!(son.standard_form, "L.f.");
+  assert_eq!(son.years().unwrap().from, Some(1741));
+
+  let active = authors.iter().find(|a| a.id == "12660-1").unwrap();
+
+  assert_eq!(
+    active.years(),
+    Some(AuthorDates {
+      from: Some(1838),
+      to: Some(1850),
+      flourished: true,
+    })
+  );
+  assert!(active.forename.is_none());
+  assert!(active.alternative_names.is_empty());
 }
 
 #[tokio::test]
 async fn filter_by_family() {
-  let res = Ipni::new()
+  let res = client()
+    .ipni()
     .query(terms::Name::Family, "Poaceae")
     .filter(Filters::Familial)
     .run()
@@ -108,7 +393,7 @@ async fn filter_by_family() {
 
 #[tokio::test]
 async fn filter_by_infrafamily() {
-  let base = Ipni::new().query(terms::Name::Family, "Poaceae");
+  let base = client().ipni().query(terms::Name::Family, "Poaceae");
 
   let unfiltered = base.clone().run().await.unwrap();
   let filtered = base
@@ -123,7 +408,7 @@ async fn filter_by_infrafamily() {
 
 #[tokio::test]
 async fn filter_by_generic() {
-  let base = Ipni::new().query(terms::Name::Family, "Poaceae");
+  let base = client().ipni().query(terms::Name::Family, "Poaceae");
 
   let unfiltered = base.clone().run().await.unwrap();
   let filtered = base.clone().filter(Filters::Generic).run().await.unwrap();
@@ -133,7 +418,7 @@ async fn filter_by_generic() {
 
 #[tokio::test]
 async fn filter_by_infrageneric() {
-  let base = Ipni::new().query(terms::Name::Family, "Poaceae");
+  let base = client().ipni().query(terms::Name::Family, "Poaceae");
 
   let unfiltered = base.clone().run().await.unwrap();
   let filtered = base
@@ -148,7 +433,7 @@ async fn filter_by_infrageneric() {
 
 #[tokio::test]
 async fn filter_by_specific() {
-  let base = Ipni::new().query(terms::Name::Family, "Poaceae");
+  let base = client().ipni().query(terms::Name::Family, "Poaceae");
 
   let unfiltered = base.clone().run().await.unwrap();
   let filtered = base.clone().filter(Filters::Specific).run().await.unwrap();
@@ -158,7 +443,7 @@ async fn filter_by_specific() {
 
 #[tokio::test]
 async fn filter_by_infraspecific() {
-  let base = Ipni::new().query(terms::Name::Family, "Poaceae");
+  let base = client().ipni().query(terms::Name::Family, "Poaceae");
 
   let unfiltered = base.clone().run().await.unwrap();
   let filtered = base
@@ -173,7 +458,84 @@ async fn filter_by_infraspecific() {
 
 #[tokio::test]
 async fn suggest() {
-  let res = Ipni::suggest("Poa ann".into()).await.unwrap();
+  let res = client().ipni().suggest("Poa ann".into()).await.unwrap();
 
   assert_eq!(res.suggested_terms.scientific_name[0], "Poa annua");
 }
+
+#[tokio::test]
+async fn lookup_name_not_found() {
+  let err = client()
+    .ipni()
+    .lookup_name(&"1-1".parse().unwrap())
+    .await
+    .unwrap_err();
+
+  assert!(matches!(err, Error::Transport(_)));
+
+  let transport =
+    MemoryTransport::new().respond("/api/1/n/1-1", HttpResponse::new(StatusCode::NOT_FOUND, ""));
+  let client = KewClient::builder().transport(transport).build().unwrap();
+  let err = client
+    .ipni()
+    .lookup_name(&"1-1".parse().unwrap())
+    .await
+    .unwrap_err();
+
+  assert!(matches!(err, Error::NotFound { id } if id == "1-1"));
+
+  // Only record lookups have an id that can be missing.
+  let transport = MemoryTransport::new().respond(
+    "/api/1/search?perPage=500&cursor=*&q=Poa",
+    HttpResponse::new(StatusCode::NOT_FOUND, "no such endpoint"),
+  );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+  let err = client.ipni().search("Poa".into()).await.unwrap_err();
+
+  assert!(matches!(
+    err,
+    Error::Http { status: StatusCode::NOT_FOUND, body } if body == "no such endpoint"
+  ));
+}
+
+#[tokio::test]
+async fn retry_rate_limited() {
+  let mut limited = HttpResponse::new(StatusCode::TOO_MANY_REQUESTS, "");
+  limited.headers.insert(RETRY_AFTER, "0".parse().unwrap());
+
+  let transport = MemoryTransport::new()
+    .respond("/api/1/a/12653-1", limited.clone())
+    .respond("/api/1/a/12653-1", limited)
+    .json("/api/1/a/12653-1", fixture!("lookup_author_12653-1"));
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  let res = client
+    .ipni()
+    .lookup_author(&"12653-1".parse().unwrap())
+    .await
+    .unwrap();
+
+  assert_eq!(res.standard_form, "L.");
+
+  let transport = MemoryTransport::new().respond(
+    "/api/1/a/12653-1",
+    HttpResponse::new(StatusCode::TOO_MANY_REQUESTS, ""),
+  );
+  let client = KewClient::builder()
+    .transport(transport)
+    .retry(RetryPolicy {
+      max_retries: 2,
+      base_delay: Duration::from_millis(1),
+      max_delay: Duration::from_millis(1),
+    })
+    .build()
+    .unwrap();
+
+  let err = client
+    .ipni()
+    .lookup_author(&"12653-1".parse().unwrap())
+    .await
+    .unwrap_err();
+
+  assert!(matches!(err, Error::RateLimited { retry_after: None }));
+}
@@ -1,22 +1,40 @@
+mod batch;
+mod cache;
+mod client;
 pub(crate) mod core;
+mod extra;
+mod flight;
+mod harvest;
+mod rate_limit;
+mod retry;
 
 macro_rules! impl_api {
-  ($ty:ty, URL: $url:path, Filters: $filters:path, Ok: $ok:path, Query: $query:path) => {
+  ($ty:ty, Service: $service:expr, Filters: $filters:path, Ok: $ok:path, Query: $query:path) => {
     impl Api for $ty {
       type Filters = $filters;
       type Ok = $ok;
       type Query = $query;
 
-      const URL: &'static str = $url;
+      const SERVICE: Service = $service;
 
-      fn new() -> Self {
+      fn with_client(client: KewClient) -> Self {
         Self {
+          client,
           query: None,
           filters: None,
+          expr: None,
           cursor: None,
+          per_page: None,
+          limit: None,
         }
       }
 
+      fn client(mut self, client: KewClient) -> Self {
+        self.client = client;
+
+        self
+      }
+
       fn query(mut self, key: impl Into<Self::Query>, value: impl Into<String>) -> Self {
         let mut query = self.query.unwrap_or_default();
         query.push((key.into(), value.into()));
@@ -25,6 +43,15 @@ macro_rules! impl_api {
         self
       }
 
+      fn expr(mut self, expr: Query<Self::Query>) -> Self {
+        self.expr = Some(match self.expr {
+          Some(existing) => existing.and(expr),
+          None => expr,
+        });
+
+        self
+      }
+
       fn filter(mut self, filter: Filters) -> Self {
         let mut filters = self.filters.unwrap_or_default();
         filters.push(filter);
@@ -39,20 +66,135 @@ macro_rules! impl_api {
         self
       }
 
+      fn per_page(mut self, per_page: usize) -> Self {
+        self.per_page = Some(per_page);
+
+        self
+      }
+
+      fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+
+        self
+      }
+
       async fn run(&self) -> Result<SearchResponse<Self::Ok>, Error> {
-        let query = self.query.clone().map(|q| SearchQuery::Vec(q));
-        let filters = self.filters.clone().map(|f| {
+        self.send_search(self.search_query()).await
+      }
+
+      fn stream(&self) -> impl Stream<Item = Result<Self::Ok, Error>> + Send + use<> {
+        let pages = stream::try_unfold(Some(self.clone()), |api| async move {
+          let Some(api) = api else {
+            return Ok(None);
+          };
+
+          let page = api.run().await?;
+          let next = next_cursor(api.cursor.as_deref(), &page).map(|c| api.cursor(c));
+          let results = stream::iter(page.results.into_iter().map(Ok));
+
+          Ok::<_, Error>(Some((results, next)))
+        });
+
+        pages.try_flatten().take(self.limit.unwrap_or(usize::MAX))
+      }
+
+      fn harvest(&self) -> impl Stream<Item = Result<HarvestPage<Self::Ok>, Error>> + Send + use<> {
+        let cursor = self.cursor.clone().unwrap_or_else(|| "*".into());
+        let state = HarvestState::new(
+          Self::SERVICE,
+          self.query_param(),
+          self.filters_param(),
+          cursor,
+        );
+
+        self.harvest_from(state)
+      }
+
+      fn resume(
+        &self,
+        state: HarvestState,
+      ) -> Result<impl Stream<Item = Result<HarvestPage<Self::Ok>, Error>> + Send + use<>, Error>
+      {
+        state.validate(Self::SERVICE, &self.query_param(), &self.filters_param())?;
+
+        Ok(self.harvest_from(state))
+      }
+    }
+
+    impl $ty {
+      fn search_query(&self) -> Option<SearchQuery<$query>> {
+        match (self.query.clone(), self.expr.clone()) {
+          (None, None) => None,
+          (query, expr) => Some(SearchQuery::Terms(query.unwrap_or_default(), expr)),
+        }
+      }
+
+      fn query_param(&self) -> Option<String> {
+        self.search_query().map(|q| q.format())
+      }
+
+      fn filters_param(&self) -> Option<String> {
+        self.filters().map(|f| f.join(","))
+      }
+
+      fn filters(&self) -> Option<Vec<String>> {
+        self.filters.clone().map(|f| {
           f.into_iter()
             .map(|f| {
               let f: &'static str = f.into();
               String::from(f)
             })
             .collect()
-        });
+        })
+      }
+
+      fn harvest_from(
+        &self,
+        state: HarvestState,
+      ) -> impl Stream<Item = Result<HarvestPage<$ok>, Error>> + Send + use<> {
+        let limit = self.limit.unwrap_or(usize::MAX);
+
+        stream::try_unfold((self.clone(), state), move |(api, mut state)| async move {
+          if state.finished || state.fetched >= limit {
+            return Ok(None);
+          }
+
+          let api = api.cursor(state.cursor.clone());
+          let mut page = api.run().await?;
+          page.results.truncate(limit - state.fetched);
+
+          state.fetched += page.results.len();
+          match next_cursor(Some(&state.cursor), &page) {
+            Some(cursor) => state.cursor = cursor,
+            None => state.finished = true,
+          }
+
+          let page = HarvestPage {
+            results: page.results,
+            state: state.clone(),
+          };
+
+          Ok::<_, Error>(Some((page, (api, state))))
+        })
+      }
+
+      async fn send_search(
+        &self,
+        query: Option<SearchQuery<$query>>,
+      ) -> Result<SearchResponse<$ok>, Error> {
+        let filters = self.filters();
         let cursor = self.cursor.as_deref().unwrap_or("*");
-        let params = build_params(&query, &filters, &cursor);
+        let per_page = self.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        let per_page = self.limit.map_or(per_page, |limit| per_page.min(limit));
+        let params = build_params(&query, &filters, &cursor, per_page);
+
+        let mut res: SearchResponse<$ok> =
+          get(&self.client, Self::SERVICE, "search", params).await?;
+        if let Some(limit) = self.limit {
+          res.results.truncate(limit);
+        }
 
-        get(Self::URL, "search", params).await
+        Ok(res)
       }
     }
   };
@@ -60,7 +202,21 @@ macro_rules! impl_api {
 
 pub mod ipni;
 pub mod powo;
+pub mod query;
+pub mod transport;
+
+use std::time::Duration;
 
+pub use batch::Batch;
+pub use cache::{Cache, CacheStats, Endpoint};
+pub use client::{KewClient, KewClientBuilder, Service};
+pub use extra::{ExtraFields, UnknownFields};
+use futures::Stream;
+pub use harvest::{HarvestPage, HarvestState};
+use query::Query;
+pub use rate_limit::RateLimit;
+use reqwest::StatusCode;
+pub use retry::RetryPolicy;
 use serde::{Deserialize, de::DeserializeOwned};
 
 #[derive(Debug, thiserror::Error)]
@@ -69,22 +225,85 @@ pub enum Error {
   Reqwest(#[from] reqwest::Error),
   #[error(transparent)]
   Url(#[from] url::ParseError),
+  #[error(transparent)]
+  Transport(Box<dyn std::error::Error + Send + Sync>),
+  #[error(transparent)]
+  Io(#[from] std::io::Error),
+  #[error("invalid cassette: {0}")]
+  Cassette(serde_json::Error),
+  #[error("invalid harvest state: {0}")]
+  HarvestState(serde_json::Error),
+  #[error("harvest state does not match this query: {0}")]
+  HarvestMismatch(String),
+  #[error(transparent)]
+  Parse(#[from] query::ParseError),
+  #[error(transparent)]
+  Id(#[from] ipni::IdError),
+  #[error("no record found for {id:?}")]
+  NotFound { id: String },
+  #[error("rate limited (retry after {retry_after:?})")]
+  RateLimited { retry_after: Option<Duration> },
+  #[error("server returned {status}: {body}")]
+  Http { status: StatusCode, body: String },
+  #[error("failed to decode response at `{path}`: {source} (near {raw_snippet:?})")]
+  Decode {
+    path: String,
+    raw_snippet: String,
+    source: serde_json::Error,
+  },
+  /// Only returned by clients built with `KewClientBuilder::strict`.
+  #[error("{0}")]
+  UnknownFields(UnknownFields),
+  /// Returned by clients built with `KewClientBuilder::offline` when a
+  /// response isn't in the cache. `url` is what would have been fetched.
+  #[error("offline and not cached: {url}")]
+  OfflineMiss { url: url::Url },
+  #[error("invalid user agent: {0:?}")]
+  UserAgent(String),
+  #[error("{0:?} is not in the WGSRPD table")]
+  UnknownRegion(powo::wgsrpd::Region),
+  #[error("invalid rate limit: {per_second} requests per second")]
+  InvalidRateLimit { per_second: f64 },
 }
 
-pub trait Api {
-  const URL: &'static str;
-  type Ok: DeserializeOwned + Clone;
+pub trait Api: Sized {
+  const SERVICE: Service;
+  type Ok: DeserializeOwned + Clone + ExtraFields;
   type Query;
   type Filters;
 
-  fn new() -> Self;
+  /// Builds a query that runs through the process-wide `KewClient::shared`.
+  fn new() -> Self {
+    Self::with_client(KewClient::shared())
+  }
+  fn with_client(client: KewClient) -> Self;
+  fn client(self, client: KewClient) -> Self;
   fn query(self, key: impl Into<Self::Query>, value: impl Into<String>) -> Self;
+  /// Adds a boolean expression, combined with any other terms using `AND`.
+  fn expr(self, expr: Query<Self::Query>) -> Self;
   fn filter(self, filter: Self::Filters) -> Self;
   fn cursor(self, cursor: String) -> Self;
+  /// Results requested per page. Defaults to 500.
+  fn per_page(self, per_page: usize) -> Self;
+  /// The most results to return, whether from a single `run` or across every
+  /// page of a `stream`.
+  fn limit(self, limit: usize) -> Self;
   fn run(&self) -> impl Future<Output = Result<SearchResponse<Self::Ok>, Error>>;
+  /// Runs the query and follows the returned cursors, yielding every result
+  /// until a page comes back empty or the cursor stops changing.
+  fn stream(&self) -> impl Stream<Item = Result<Self::Ok, Error>> + Send + use<Self>;
+  /// Walks the cursors like `stream`, but yields whole pages along with a
+  /// `HarvestState` that can be saved and later passed to `resume`.
+  fn harvest(&self) -> impl Stream<Item = Result<HarvestPage<Self::Ok>, Error>> + Send + use<Self>;
+  /// Continues a harvest from a saved state, failing if the state was saved
+  /// by a different query or set of filters.
+  fn resume(
+    &self,
+    state: HarvestState,
+  ) -> Result<impl Stream<Item = Result<HarvestPage<Self::Ok>, Error>> + Send + use<Self>, Error>;
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct SearchResponse<R: Clone> {
   #[serde(rename(deserialize = "totalResults"))]
   total_results: Option<i32>,
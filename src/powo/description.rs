@@ -0,0 +1,166 @@
+use std::{collections::HashMap, fmt};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{Characteristic, PowoLookup, html};
+use crate::{
+  ExtraFields,
+  extra::{collect_extra, join},
+};
+
+/// What a description describes. Keys POWO uses that aren't a searchable
+/// `Characteristic`, such as `general`, are kept as `Other`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum DescriptionKey {
+  Characteristic(Characteristic),
+  Other(String),
+}
+
+impl DescriptionKey {
+  pub fn as_str(&self) -> &str {
+    match self {
+      DescriptionKey::Characteristic(characteristic) => (*characteristic).into(),
+      DescriptionKey::Other(key) => key,
+    }
+  }
+}
+
+impl From<Characteristic> for DescriptionKey {
+  fn from(val: Characteristic) -> Self {
+    DescriptionKey::Characteristic(val)
+  }
+}
+
+impl From<String> for DescriptionKey {
+  fn from(key: String) -> Self {
+    Characteristic::ALL
+      .into_iter()
+      .find(|characteristic| <&str>::from(*characteristic).eq_ignore_ascii_case(&key))
+      .map_or(DescriptionKey::Other(key), DescriptionKey::Characteristic)
+  }
+}
+
+impl From<DescriptionKey> for String {
+  fn from(key: DescriptionKey) -> Self {
+    match key {
+      DescriptionKey::Other(key) => key,
+      key => key.as_str().to_owned(),
+    }
+  }
+}
+
+impl fmt::Display for DescriptionKey {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Description {
+  /// The description as POWO returns it, which may contain HTML.
+  pub description: String,
+  pub source: Option<String>,
+
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
+}
+
+impl Description {
+  pub fn html(&self) -> &str {
+    &self.description
+  }
+
+  /// The description with markup removed and entities decoded.
+  pub fn text(&self) -> String {
+    html::to_text(&self.description)
+  }
+
+  /// The description with bold, italics, links and paragraphs converted to
+  /// Markdown.
+  pub fn markdown(&self) -> String {
+    html::to_markdown(&self.description)
+  }
+}
+
+/// The descriptions of a taxon from one source.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Descriptions {
+  #[serde(rename(deserialize = "asTaxon"))]
+  pub as_taxon: String,
+  pub source: String,
+  #[serde(rename(deserialize = "fromSynonym"))]
+  pub from_synonym: bool,
+  pub descriptions: HashMap<DescriptionKey, Vec<Description>>,
+
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
+}
+
+impl Descriptions {
+  pub fn get(&self, key: impl Into<DescriptionKey>) -> &[Description] {
+    self
+      .descriptions
+      .get(&key.into())
+      .map_or(&[], Vec::as_slice)
+  }
+}
+
+/// A description along with where it came from.
+#[derive(Debug, Clone, Copy)]
+pub struct SourcedDescription<'a> {
+  pub description: &'a Description,
+  /// The description's own source if it has one, or else that of the
+  /// descriptions it belongs to.
+  pub source: &'a str,
+  /// The name the description was written for, which may be a synonym.
+  pub as_taxon: &'a str,
+}
+
+impl PowoLookup {
+  /// Every description of `key` across all sources, ordered by source.
+  /// Empty unless `LookupField::Descriptions` was requested.
+  pub fn descriptions_of(&self, key: impl Into<DescriptionKey>) -> Vec<SourcedDescription<'_>> {
+    let Some(sources) = self.descriptions.get() else {
+      return vec![];
+    };
+    let key = key.into();
+
+    let mut names = sources.keys().collect::<Vec<_>>();
+    names.sort();
+
+    names
+      .into_iter()
+      .flat_map(|name| {
+        let descriptions = &sources[name];
+        descriptions
+          .get(key.clone())
+          .iter()
+          .map(move |description| SourcedDescription {
+            description,
+            source: description
+              .source
+              .as_deref()
+              .unwrap_or(&descriptions.source),
+            as_taxon: &descriptions.as_taxon,
+          })
+      })
+      .collect()
+  }
+}
+
+impl ExtraFields for Description {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+  }
+}
+
+impl ExtraFields for Descriptions {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+    self
+      .descriptions
+      .collect_unknown(&join(path, "descriptions"), out);
+  }
+}
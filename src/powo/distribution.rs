@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+  PowoLookup,
+  wgsrpd::{self, Area, Level},
+};
+
+string_enum!(
+  /// How a taxon came to be in an area.
+  Establishment {
+    Native => "Native",
+    Introduced => "Introduced",
+    Extinct => "Extinct",
+    Doubtful => "Doubtful",
+    LocationDoubtful => "Location_Doubtful",
+  }
+);
+
+/// A WGSRPD level 3 area a taxon occurs in.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Distribution {
+  pub name: String,
+  pub establishment: Option<Establishment>,
+  #[serde(rename(deserialize = "featureId"))]
+  pub feature_id: Option<String>,
+  #[serde(rename(deserialize = "tdwgCode"))]
+  pub tdwg_code: Option<String>,
+  #[serde(rename(deserialize = "tdwgLevel"))]
+  pub tdwg_level: Option<u8>,
+}
+
+impl Distribution {
+  /// This entry's area in the bundled WGSRPD table, found by code or, for
+  /// entries without one, by name.
+  pub fn area(&self) -> Option<&'static Area> {
+    match &self.tdwg_code {
+      Some(code) => wgsrpd::area(code),
+      None => wgsrpd::find(&self.name),
+    }
+  }
+
+  /// The level 2 region containing this area.
+  pub fn region(&self) -> Option<&'static Area> {
+    self.area()?.region()
+  }
+
+  /// The level 1 continent containing this area.
+  pub fn continent(&self) -> Option<&'static Area> {
+    self.area()?.continent()
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DistributionMap {
+  #[serde(default)]
+  pub natives: Vec<Distribution>,
+  #[serde(default)]
+  pub introduced: Vec<Distribution>,
+  #[serde(default)]
+  pub extinct: Vec<Distribution>,
+  #[serde(default)]
+  pub doubtful: Vec<Distribution>,
+  #[serde(default, rename(deserialize = "locationDoubtful"))]
+  pub location_doubtful: Vec<Distribution>,
+}
+
+impl DistributionMap {
+  /// Every entry, whatever its establishment.
+  pub fn all(&self) -> impl Iterator<Item = &Distribution> {
+    self
+      .natives
+      .iter()
+      .chain(&self.introduced)
+      .chain(&self.extinct)
+      .chain(&self.doubtful)
+      .chain(&self.location_doubtful)
+  }
+
+  /// The distinct areas at `level` covered by `entries`, in table order.
+  /// Rolling up to `Level::Continent` gives the continents a taxon is
+  /// found on.
+  pub fn roll_up<'a>(
+    entries: impl IntoIterator<Item = &'a Distribution>,
+    level: Level,
+  ) -> Vec<&'static Area> {
+    let mut areas = entries
+      .into_iter()
+      .filter_map(|entry| entry.area()?.ancestor(level))
+      .collect::<Vec<_>>();
+
+    let position = |area: &&Area| {
+      wgsrpd::areas()
+        .iter()
+        .position(|other| std::ptr::eq(other, *area))
+    };
+    areas.sort_by_key(position);
+    areas.dedup_by(|a, b| std::ptr::eq(*a, *b));
+
+    areas
+  }
+}
+
+impl PowoLookup {
+  /// `locations` resolved against the bundled WGSRPD table. Names that
+  /// aren't in the table are skipped.
+  pub fn location_areas(&self) -> Vec<&'static Area> {
+    self
+      .locations
+      .iter()
+      .flatten()
+      .filter_map(|name| wgsrpd::find(name))
+      .collect()
+  }
+}
@@ -0,0 +1,212 @@
+// Just enough HTML handling for POWO descriptions, which use a handful of
+// inline tags (`<b>`, `<i>`, `<a>`, `<p>`, `<br>`) and named entities.
+enum Token<'a> {
+  Text(String),
+  Start { name: String, href: Option<&'a str> },
+  End(String),
+}
+
+fn tokenize(html: &str) -> Vec<Token<'_>> {
+  let mut tokens = vec![];
+  let mut rest = html;
+
+  while !rest.is_empty() {
+    let Some(start) = rest.find('<') else {
+      tokens.push(Token::Text(decode_entities(rest)));
+      break;
+    };
+    // Only `<` followed by a letter, `/` or `!` can start a tag, so text like
+    // `petals < 5 mm` is kept.
+    let starts_tag =
+      rest[start + 1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!');
+    if !starts_tag {
+      tokens.push(Token::Text(decode_entities(&rest[..=start])));
+      rest = &rest[start + 1..];
+      continue;
+    }
+
+    if start > 0 {
+      tokens.push(Token::Text(decode_entities(&rest[..start])));
+    }
+
+    let Some(end) = rest[start..].find('>') else {
+      // A stray `<`, which can't start a tag.
+      tokens.push(Token::Text(decode_entities(&rest[start..])));
+      break;
+    };
+    let tag = &rest[start + 1..start + end];
+    rest = &rest[start + end + 1..];
+
+    if let Some(name) = tag.strip_prefix('/') {
+      tokens.push(Token::End(name.trim().to_ascii_lowercase()));
+    } else {
+      let tag = tag.trim_end_matches('/');
+      let name = tag
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+      tokens.push(Token::Start {
+        name,
+        href: attribute(tag, "href"),
+      });
+    }
+  }
+
+  tokens
+}
+
+fn attribute<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+  let at = tag.find(&format!("{}=", name))? + name.len() + 1;
+  let value = &tag[at..];
+  let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+  let value = &value[1..];
+
+  Some(&value[..value.find(quote)?])
+}
+
+fn decode_entities(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  let mut rest = text;
+
+  while let Some(start) = rest.find('&') {
+    out.push_str(&rest[..start]);
+    rest = &rest[start..];
+
+    let decoded = rest
+      .find(';')
+      .filter(|end| *end <= 10)
+      .and_then(|end| Some((entity(&rest[1..end])?, end)));
+    match decoded {
+      Some((c, end)) => {
+        out.push(c);
+        rest = &rest[end + 1..];
+      },
+      None => {
+        out.push('&');
+        rest = &rest[1..];
+      },
+    }
+  }
+  out.push_str(rest);
+
+  out
+}
+
+fn entity(name: &str) -> Option<char> {
+  if let Some(code) = name.strip_prefix('#') {
+    let code = match code.strip_prefix(['x', 'X']) {
+      Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+      None => code.parse().ok()?,
+    };
+    return char::from_u32(code);
+  }
+
+  Some(match name {
+    "amp" => '&',
+    "lt" => '<',
+    "gt" => '>',
+    "quot" => '"',
+    "apos" => '\'',
+    "nbsp" => ' ',
+    "ndash" => '–',
+    "mdash" => '—',
+    "deg" => '°',
+    "times" => '×',
+    "plusmn" => '±',
+    "micro" => 'µ',
+    "frac12" => '½',
+    "le" => '≤',
+    "ge" => '≥',
+    "lsquo" => '‘',
+    "rsquo" => '’',
+    "ldquo" => '“',
+    "rdquo" => '”',
+    _ => return None,
+  })
+}
+
+/// Collapses runs of whitespace within lines and trims blank lines, as a
+/// browser would when rendering.
+fn tidy(text: &str) -> String {
+  text
+    .split("\n\n")
+    .map(|paragraph| {
+      paragraph
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+    })
+    .filter(|paragraph| !paragraph.is_empty())
+    .collect::<Vec<_>>()
+    .join("\n\n")
+}
+
+fn is_block(name: &str) -> bool {
+  matches!(name, "p" | "div" | "ul" | "ol")
+}
+
+pub(crate) fn to_text(html: &str) -> String {
+  let mut out = String::new();
+
+  for token in tokenize(html) {
+    match token {
+      Token::Text(text) => out.push_str(&text),
+      Token::Start { name, .. } if name == "br" => out.push('\n'),
+      Token::Start { name, .. } if name == "li" => out.push('\n'),
+      Token::Start { name, .. } | Token::End(name) if is_block(&name) => out.push_str("\n\n"),
+      _ => {},
+    }
+  }
+
+  tidy(&out)
+}
+
+pub(crate) fn to_markdown(html: &str) -> String {
+  let mut out = String::new();
+  let mut links = vec![];
+
+  for token in tokenize(html) {
+    match token {
+      Token::Text(text) => out.push_str(&escape_markdown(&text)),
+      Token::Start { name, href } => match name.as_str() {
+        "b" | "strong" => out.push_str("**"),
+        "i" | "em" => out.push('*'),
+        "br" => out.push('\n'),
+        "li" => out.push_str("\n- "),
+        "a" => {
+          out.push('[');
+          links.push(href);
+        },
+        name if is_block(name) => out.push_str("\n\n"),
+        _ => {},
+      },
+      Token::End(name) => match name.as_str() {
+        "b" | "strong" => out.push_str("**"),
+        "i" | "em" => out.push('*'),
+        "a" => match links.pop().flatten() {
+          Some(href) => out.push_str(&format!("]({})", href)),
+          None => out.push(']'),
+        },
+        name if is_block(name) => out.push_str("\n\n"),
+        _ => {},
+      },
+    }
+  }
+
+  tidy(&out)
+}
+
+fn escape_markdown(text: &str) -> String {
+  let mut out = String::with_capacity(text.len());
+  for c in text.chars() {
+    if matches!(c, '\\' | '*' | '_' | '[' | ']' | '`') {
+      out.push('\\');
+    }
+    out.push(c);
+  }
+
+  out
+}
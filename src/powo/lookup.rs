@@ -0,0 +1,163 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use urn::Urn;
+
+use super::PowoLookup;
+use crate::{Error, ExtraFields, KewClient, Service, core::get};
+
+/// An optional section of a taxon record, requested with `Lookup::with`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum LookupField {
+  Distribution,
+  DistributionEnvelope,
+  Descriptions,
+  Images,
+  References,
+}
+
+impl LookupField {
+  pub const ALL: [LookupField; 5] = [
+    LookupField::Distribution,
+    LookupField::DistributionEnvelope,
+    LookupField::Descriptions,
+    LookupField::Images,
+    LookupField::References,
+  ];
+}
+
+impl From<LookupField> for &'static str {
+  fn from(val: LookupField) -> Self {
+    match val {
+      LookupField::Distribution => "distribution",
+      LookupField::DistributionEnvelope => "distributionEnvelope",
+      LookupField::Descriptions => "descriptions",
+      LookupField::Images => "images",
+      LookupField::References => "references",
+    }
+  }
+}
+
+/// An optional section of a `PowoLookup`, which tells a section that wasn't
+/// asked for apart from one the server had nothing for.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub enum Section<T> {
+  #[default]
+  NotRequested,
+  /// Requested, but not in the response.
+  Missing,
+  Present(T),
+}
+
+impl<T> Section<T> {
+  pub fn get(&self) -> Option<&T> {
+    match self {
+      Section::Present(value) => Some(value),
+      _ => None,
+    }
+  }
+
+  pub fn into_inner(self) -> Option<T> {
+    match self {
+      Section::Present(value) => Some(value),
+      _ => None,
+    }
+  }
+
+  pub fn is_requested(&self) -> bool {
+    !matches!(self, Section::NotRequested)
+  }
+
+  pub(crate) fn mark_requested(&mut self) {
+    if let Section::NotRequested = self {
+      *self = Section::Missing;
+    }
+  }
+}
+
+/// An explicit `null` is read like an absent field, as `NotRequested`. Only
+/// `Lookup::send` knows which sections were asked for, and marks those it
+/// didn't get as `Missing`.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Section<T> {
+  fn deserialize<D>(de: D) -> Result<Self, D::Error>
+  where
+    D: Deserializer<'de>,
+  {
+    Ok(match Option::<T>::deserialize(de)? {
+      Some(value) => Section::Present(value),
+      None => Section::NotRequested,
+    })
+  }
+}
+
+impl<T: Serialize> Serialize for Section<T> {
+  fn serialize<S>(&self, ser: S) -> Result<S::Ok, S::Error>
+  where
+    S: Serializer,
+  {
+    self.get().serialize(ser)
+  }
+}
+
+impl<T: ExtraFields> ExtraFields for Section<T> {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    if let Section::Present(value) = self {
+      value.collect_unknown(path, out);
+    }
+  }
+}
+
+/// A taxon lookup, built with `Powo::lookup`.
+#[derive(Debug, Clone)]
+pub struct Lookup {
+  client: KewClient,
+  id: Urn,
+  fields: Vec<LookupField>,
+}
+
+impl Lookup {
+  pub(crate) fn new(client: KewClient, id: Urn) -> Self {
+    Self {
+      client,
+      id,
+      fields: vec![],
+    }
+  }
+
+  /// Includes an optional section in the response.
+  pub fn with(mut self, field: LookupField) -> Self {
+    if !self.fields.contains(&field) {
+      self.fields.push(field);
+    }
+
+    self
+  }
+
+  pub async fn send(&self) -> Result<PowoLookup, Error> {
+    let params = if self.fields.is_empty() {
+      vec![]
+    } else {
+      // Always in the same order, so the same sections make the same URL
+      // whatever order they were asked for in.
+      let fields = LookupField::ALL
+        .into_iter()
+        .filter(|field| self.fields.contains(field))
+        .map(<&str>::from)
+        .collect::<Vec<_>>();
+
+      vec![("fields".into(), fields.join(","))]
+    };
+
+    let mut res: PowoLookup = get(
+      &self.client,
+      Service::Powo,
+      format!("taxon/{}", self.id),
+      params.into_iter(),
+    )
+    .await?;
+
+    for field in &self.fields {
+      res.section_requested(*field);
+    }
+
+    Ok(res)
+  }
+}
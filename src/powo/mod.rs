@@ -1,21 +1,99 @@
 use std::collections::HashMap;
 
+use futures::{Stream, StreamExt, TryStreamExt, stream};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use urn::Urn;
 
-use crate::Error;
+use crate::{
+  Error,
+  extra::{ExtraFields, collect_extra, join},
+};
 
 pub type Id = Urn;
 
 use crate::{
   Api,
+  Batch,
+  HarvestPage,
+  HarvestState,
+  KewClient,
   SearchResponse,
-  core::{SearchQuery, build_params, get},
+  Service,
+  core::{DEFAULT_PER_PAGE, SearchQuery, build_params, get, next_cursor},
+  query::Query,
 };
 
+/// Defines an enum over the strings POWO uses for a field, with an `Other`
+/// variant holding anything not (yet) known here. Values are matched
+/// case-insensitively, treating `_` and spaces alike.
+macro_rules! string_enum {
+  ($(#[$meta:meta])* $name:ident { $($variant:ident => $value:literal,)* }) => {
+    $(#[$meta])*
+    #[derive(Debug, PartialEq, Eq, Hash, Clone, serde::Deserialize, serde::Serialize)]
+    #[serde(from = "String", into = "String")]
+    pub enum $name {
+      $($variant,)*
+      Other(String),
+    }
+
+    impl $name {
+      /// The value as POWO spells it.
+      pub fn as_str(&self) -> &str {
+        match self {
+          $($name::$variant => $value,)*
+          $name::Other(value) => value,
+        }
+      }
+    }
+
+    impl From<&str> for $name {
+      fn from(value: &str) -> Self {
+        let normalized = value.trim().replace(' ', "_");
+        $(if normalized.eq_ignore_ascii_case($value) {
+          return $name::$variant;
+        })*
+
+        $name::Other(value.to_owned())
+      }
+    }
+
+    impl From<String> for $name {
+      fn from(value: String) -> Self {
+        value.as_str().into()
+      }
+    }
+
+    impl From<$name> for String {
+      fn from(value: $name) -> Self {
+        match value {
+          $name::Other(value) => value,
+          known => known.as_str().to_owned(),
+        }
+      }
+    }
+
+    impl std::fmt::Display for $name {
+      fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+      }
+    }
+  };
+}
+
+mod description;
+mod distribution;
 mod filters;
+mod html;
+mod lookup;
+mod taxonomy;
 mod terms;
-pub use terms::{Name, PowoQuery};
+pub mod wgsrpd;
+pub use description::{Description, DescriptionKey, Descriptions, SourcedDescription};
+pub use distribution::{Distribution, DistributionMap, Establishment};
+pub use lookup::{Lookup, LookupField, Section};
+pub use taxonomy::{NomenclaturalStatus, Rank, TaxonomicStatus};
+pub use terms::{Characteristic, Geography, Name, PowoQuery};
 
 use self::filters::Filters;
 
@@ -24,46 +102,62 @@ mod test;
 
 #[derive(Debug, Clone)]
 pub struct Powo {
+  client: KewClient,
   query: Option<Vec<(PowoQuery, String)>>,
+  expr: Option<Query<PowoQuery>>,
   filters: Option<Vec<Filters>>,
   cursor: Option<String>,
+  per_page: Option<usize>,
+  limit: Option<usize>,
 }
 
 impl_api!(
   Powo,
-  URL: crate::core::POWO_URL,
+  Service: Service::Powo,
   Filters: Filters,
   Ok: PowoResult,
   Query: PowoQuery
 );
 
 impl Powo {
-  pub async fn search(query: String) -> Result<SearchResponse<<Self as Api>::Ok>, Error> {
-    let query = Some(SearchQuery::<<Self as Api>::Query>::String(query));
-    let params = build_params(&query, &None::<Vec<String>>, "*");
-    get(Self::URL, "search", params).await
+  /// Runs a raw query string, using this builder's filters, cursor and page
+  /// options.
+  pub async fn search(&self, query: String) -> Result<SearchResponse<<Self as Api>::Ok>, Error> {
+    self.send_search(Some(SearchQuery::String(query))).await
   }
 
-  pub async fn lookup(id: Urn, include: Option<Vec<String>>) -> Result<PowoLookup, Error> {
-    let params = if let Some(include) = include {
-      vec![("fields".into(), include.join(","))]
-    } else {
-      vec![]
-    };
+  /// Adds a `geography` term for a WGSRPD region, combined with any other
+  /// terms using `AND`. Fails with `Error::UnknownRegion` if the region isn't
+  /// in the bundled table.
+  pub fn region(self, geography: Geography, region: wgsrpd::Region) -> Result<Self, Error> {
+    Ok(self.expr(region.term(geography)?))
+  }
 
-    get(Self::URL, format!("taxon/{}", id), params.into_iter()).await
+  /// Looks up a taxon. Optional sections are requested with `Lookup::with`
+  /// before calling `Lookup::send`.
+  pub fn lookup(&self, id: Urn) -> Lookup {
+    Lookup::new(self.client.clone(), id)
   }
-}
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct Distribution {
-  pub name: String,
-}
+  /// Looks up many taxa concurrently, each with the sections in `fields`.
+  /// See `Batch` for the options.
+  pub fn lookup_many(
+    &self,
+    ids: impl IntoIterator<Item = Urn>,
+    fields: impl IntoIterator<Item = LookupField>,
+  ) -> Batch<Urn, PowoLookup> {
+    let fields = fields.into_iter().collect::<Vec<_>>();
+    let client = self.client.clone();
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-pub struct DistributionMap {
-  pub natives: Vec<Distribution>,
-  pub introduced: Option<Vec<Distribution>>,
+    Batch::new(ids, move |id| {
+      let lookup = fields
+        .iter()
+        .fold(Lookup::new(client.clone(), id), |lookup, field| {
+          lookup.with(*field)
+        });
+      Box::pin(async move { lookup.send().await })
+    })
+  }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -74,7 +168,6 @@ pub struct Image {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Synonym {
   #[serde(rename(deserialize = "fqId"))]
   pub fq_id: Urn,
@@ -83,17 +176,19 @@ pub struct Synonym {
   pub accepted: bool,
   pub author: Option<String>,
   pub kingdom: Option<String>,
+
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct PowoResult {
   pub accepted: bool,
   pub author: Option<String>,
   pub kingdom: String,
   pub family: String,
   pub name: String,
-  pub rank: String,
+  pub rank: Rank,
   pub snippet: Option<String>,
   #[serde(rename(deserialize = "synonymOf"))]
   pub synonym_of: Option<Synonym>,
@@ -103,40 +198,26 @@ pub struct PowoResult {
 
   #[serde(default)]
   pub images: Vec<Image>,
+
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct Taxon {
   #[serde(rename(deserialize = "fqId"))]
   pub fq_id: Urn,
   pub name: String,
   pub author: String,
-  pub rank: String,
+  pub rank: Rank,
   #[serde(rename(deserialize = "taxonomicStatus"))]
-  pub taxonomic_status: String,
-}
+  pub taxonomic_status: TaxonomicStatus,
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
-pub struct Description {
-  pub description: String,
-  pub source: Option<String>,
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
-pub struct Descriptions {
-  #[serde(rename(deserialize = "asTaxon"))]
-  pub as_taxon: String,
-  pub source: String,
-  #[serde(rename(deserialize = "fromSynonym"))]
-  pub from_synonym: bool,
-  pub descriptions: HashMap<String, Vec<Description>>,
-}
-
-#[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct DistributionEnvelopeEntry {
   #[serde(deserialize_with = "json_float")]
   pub x: f64,
@@ -144,6 +225,9 @@ pub struct DistributionEnvelopeEntry {
   pub y: f64,
   #[serde(deserialize_with = "json_float")]
   pub z: f64,
+
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
 }
 
 fn json_float<'de, D>(de: D) -> Result<f64, D::Error>
@@ -168,14 +252,13 @@ where
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
-#[serde(deny_unknown_fields)]
 pub struct PowoLookup {
   pub modified: Option<String>,
   #[serde(rename(deserialize = "bibliographicCitation"))]
   pub bibliographic_citation: String,
   pub genus: String,
   #[serde(rename(deserialize = "taxonomicStatus"))]
-  pub taxonomic_status: String,
+  pub taxonomic_status: TaxonomicStatus,
   pub kingdom: String,
   pub phylum: String,
   #[serde(rename(deserialize = "clazz"))]
@@ -191,7 +274,7 @@ pub struct PowoLookup {
   #[serde(rename(deserialize = "taxonRemarks"))]
   pub taxon_remarks: Option<String>,
   #[serde(rename(deserialize = "nomenclaturalStatus"))]
-  pub nomenclatural_status: String,
+  pub nomenclatural_status: NomenclaturalStatus,
   pub lifeform: String,
   pub climate: String,
   pub hybrid: bool,
@@ -207,7 +290,7 @@ pub struct PowoLookup {
   pub authors: Option<String>,
   pub species: String,
   pub infraspecies: Option<String>,
-  pub rank: String,
+  pub rank: Rank,
   pub reference: Option<String>,
 
   pub classification: Vec<Taxon>,
@@ -216,10 +299,80 @@ pub struct PowoLookup {
   pub basionym: Option<Taxon>,
   pub synonyms: Option<Vec<Taxon>>,
 
-  pub distribution: Option<DistributionMap>,
-  #[serde(rename(deserialize = "distributionEnvelope"))]
-  pub distribution_envelope: Option<Vec<DistributionEnvelopeEntry>>,
-  pub descriptions: Option<HashMap<String, Descriptions>>,
+  #[serde(default)]
+  pub distribution: Section<DistributionMap>,
+  #[serde(default, rename(deserialize = "distributionEnvelope"))]
+  pub distribution_envelope: Section<Vec<DistributionEnvelopeEntry>>,
+  #[serde(default)]
+  pub descriptions: Section<HashMap<String, Descriptions>>,
+  #[serde(default)]
+  pub images: Section<Vec<Image>>,
+  /// Kept as sent, since the shape of a reference isn't modelled here.
+  #[serde(default)]
+  pub references: Section<Vec<Value>>,
 
   pub locations: Option<Vec<String>>,
+
+  #[serde(flatten, skip_serializing_if = "Map::is_empty")]
+  pub extra: Map<String, Value>,
+}
+
+impl PowoLookup {
+  fn section_requested(&mut self, field: LookupField) {
+    match field {
+      LookupField::Distribution => self.distribution.mark_requested(),
+      LookupField::DistributionEnvelope => self.distribution_envelope.mark_requested(),
+      LookupField::Descriptions => self.descriptions.mark_requested(),
+      LookupField::Images => self.images.mark_requested(),
+      LookupField::References => self.references.mark_requested(),
+    }
+  }
+}
+
+impl ExtraFields for Synonym {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+  }
+}
+
+impl ExtraFields for PowoResult {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+    self
+      .synonym_of
+      .collect_unknown(&join(path, "synonymOf"), out);
+  }
+}
+
+impl ExtraFields for Taxon {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+  }
+}
+
+impl ExtraFields for DistributionEnvelopeEntry {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+  }
+}
+
+impl ExtraFields for PowoLookup {
+  fn collect_unknown(&self, path: &str, out: &mut Vec<String>) {
+    collect_extra(&self.extra, path, out);
+    self.accepted.collect_unknown(&join(path, "accepted"), out);
+    self
+      .classification
+      .collect_unknown(&join(path, "classification"), out);
+    self
+      .basionym_of
+      .collect_unknown(&join(path, "basionymOf"), out);
+    self.basionym.collect_unknown(&join(path, "basionym"), out);
+    self.synonyms.collect_unknown(&join(path, "synonyms"), out);
+    self
+      .distribution_envelope
+      .collect_unknown(&join(path, "distributionEnvelope"), out);
+    self
+      .descriptions
+      .collect_unknown(&join(path, "descriptions"), out);
+  }
 }
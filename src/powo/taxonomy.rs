@@ -0,0 +1,111 @@
+use std::cmp::Ordering;
+
+string_enum!(
+  /// A taxonomic rank, ordered from `Kingdom` down to `Subform`. Unknown
+  /// ranks can't be compared with anything but themselves.
+  Rank {
+    Kingdom => "Kingdom",
+    Phylum => "Phylum",
+    Class => "Class",
+    Subclass => "Subclass",
+    Order => "Order",
+    Suborder => "Suborder",
+    Family => "Family",
+    Subfamily => "Subfamily",
+    Tribe => "Tribe",
+    Subtribe => "Subtribe",
+    Genus => "Genus",
+    Subgenus => "Subgenus",
+    Section => "Section",
+    Subsection => "Subsection",
+    Series => "Series",
+    Species => "Species",
+    Subspecies => "Subspecies",
+    Variety => "Variety",
+    Subvariety => "Subvariety",
+    Form => "Form",
+    Subform => "Subform",
+  }
+);
+
+string_enum!(
+  /// Whether a name is accepted, and if not, how it relates to the accepted
+  /// name.
+  TaxonomicStatus {
+    Accepted => "Accepted",
+    Synonym => "Synonym",
+    HomotypicSynonym => "Homotypic_Synonym",
+    HeterotypicSynonym => "Heterotypic_Synonym",
+    Misapplied => "Misapplied",
+    Unplaced => "Unplaced",
+    ArtificialHybrid => "Artificial_Hybrid",
+  }
+);
+
+string_enum!(
+  /// Whether a name was validly published under the nomenclatural code.
+  NomenclaturalStatus {
+    Valid => "Valid",
+    Invalid => "Invalid",
+    Illegitimate => "Illegitimate",
+    Conserved => "Conserved",
+    Rejected => "Rejected",
+    Superfluous => "Superfluous",
+  }
+);
+
+impl Rank {
+  /// Position in the hierarchy, with `Kingdom` at 0.
+  fn level(&self) -> Option<usize> {
+    Some(match self {
+      Rank::Kingdom => 0,
+      Rank::Phylum => 1,
+      Rank::Class => 2,
+      Rank::Subclass => 3,
+      Rank::Order => 4,
+      Rank::Suborder => 5,
+      Rank::Family => 6,
+      Rank::Subfamily => 7,
+      Rank::Tribe => 8,
+      Rank::Subtribe => 9,
+      Rank::Genus => 10,
+      Rank::Subgenus => 11,
+      Rank::Section => 12,
+      Rank::Subsection => 13,
+      Rank::Series => 14,
+      Rank::Species => 15,
+      Rank::Subspecies => 16,
+      Rank::Variety => 17,
+      Rank::Subvariety => 18,
+      Rank::Form => 19,
+      Rank::Subform => 20,
+      Rank::Other(_) => return None,
+    })
+  }
+
+  /// Whether this rank is below species, e.g. a subspecies or variety.
+  pub fn is_infraspecific(&self) -> bool {
+    self > &Rank::Species
+  }
+}
+
+impl PartialOrd for Rank {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    match (self.level(), other.level()) {
+      (Some(a), Some(b)) => Some(a.cmp(&b)),
+      _ => (self == other).then_some(Ordering::Equal),
+    }
+  }
+}
+
+impl TaxonomicStatus {
+  /// Whether this is any kind of synonym.
+  pub fn is_synonym(&self) -> bool {
+    matches!(
+      self,
+      TaxonomicStatus::Synonym
+        | TaxonomicStatus::HomotypicSynonym
+        | TaxonomicStatus::HeterotypicSynonym
+    )
+  }
+}
@@ -15,6 +15,21 @@ pub enum Characteristic {
   Use,
 }
 
+impl Characteristic {
+  pub const ALL: [Characteristic; 10] = [
+    Characteristic::Summary,
+    Characteristic::Appearance,
+    Characteristic::Characteristic,
+    Characteristic::Flower,
+    Characteristic::Fruit,
+    Characteristic::Leaf,
+    Characteristic::Inflorescence,
+    Characteristic::Seed,
+    Characteristic::Cloning,
+    Characteristic::Use,
+  ];
+}
+
 impl From<Characteristic> for &'static str {
   fn from(val: Characteristic) -> Self {
     match val {
@@ -5,6 +5,10 @@ pub enum Geography {
   Distribution,
 }
 
+impl Geography {
+  pub const ALL: [Geography; 1] = [Geography::Distribution];
+}
+
 impl From<Geography> for &'static str {
   fn from(val: Geography) -> Self {
     match val {
@@ -1,4 +1,6 @@
-use crate::core::ToKey;
+use std::str::FromStr;
+
+use crate::query::{ParseError, ToKey};
 mod characteristic;
 mod geography;
 mod name;
@@ -27,4 +29,20 @@ impl ToKey for PowoQuery {
   fn to_key(&self) -> &'static str {
     (*self).into()
   }
+
+  fn keys() -> impl Iterator<Item = Self> {
+    Characteristic::ALL
+      .into_iter()
+      .map(PowoQuery::from)
+      .chain(Geography::ALL.into_iter().map(PowoQuery::from))
+      .chain(Name::ALL.into_iter().map(PowoQuery::from))
+  }
+}
+
+impl FromStr for PowoQuery {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, ParseError> {
+    Self::from_key(s)
+  }
 }
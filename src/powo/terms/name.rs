@@ -11,6 +11,18 @@ pub enum Name {
   Author,
 }
 
+impl Name {
+  pub const ALL: [Name; 7] = [
+    Name::FullName,
+    Name::CommonName,
+    Name::Kingdom,
+    Name::Family,
+    Name::Genus,
+    Name::Species,
+    Name::Author,
+  ];
+}
+
 impl From<Name> for &'static str {
   fn from(val: Name) -> Self {
     match val {
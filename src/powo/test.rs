@@ -1,13 +1,94 @@
-use std::str::FromStr;
+use std::{
+  pin::pin,
+  str::FromStr,
+  sync::{Arc, Mutex},
+};
 
+use futures::{StreamExt, TryStreamExt};
 use urn::Urn;
 
-use super::{filters::Filters, terms, Powo};
-use crate::Api;
+use super::{
+  Characteristic,
+  Description,
+  DescriptionKey,
+  DistributionMap,
+  Establishment,
+  LookupField,
+  NomenclaturalStatus,
+  Rank,
+  Section,
+  TaxonomicStatus,
+  filters::Filters,
+  terms,
+  wgsrpd::{self, Level, Region},
+};
+use crate::{
+  Api,
+  Error,
+  ExtraFields,
+  HarvestState,
+  KewClient,
+  KewClientBuilder,
+  transport::{CassetteTransport, MemoryTransport},
+};
+
+macro_rules! fixture {
+  ($name:literal) => {
+    include_str!(concat!("../../fixtures/powo/", $name, ".json"))
+  };
+}
+
+fn transport() -> MemoryTransport {
+  MemoryTransport::new()
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=Poa Annua",
+      fixture!("search_poa_annua"),
+    )
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=genus:Poa,species:annua,author:L.",
+      fixture!("search_poa_annua_l"),
+    )
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=flower:yellow,leaf:alternate",
+      fixture!("search_flower_yellow_leaf_alternate"),
+    )
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=location:Africa",
+      fixture!("search_location_africa"),
+    )
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=family:Poaceae",
+      fixture!("search_family_poaceae"),
+    )
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=family:Poaceae&f=accepted_names",
+      fixture!("search_family_poaceae_accepted"),
+    )
+    .json(
+      "/api/2/taxon/urn:lsid:ipni.org:names:650591-1",
+      fixture!("lookup_650591-1"),
+    )
+    .json(
+      "/api/2/taxon/urn:lsid:ipni.org:names:650591-1?fields=distribution,descriptions",
+      fixture!("lookup_650591-1_distribution_descriptions"),
+    )
+    .json(
+      "/api/2/taxon/urn:lsid:ipni.org:names:77170908-1",
+      fixture!("lookup_77170908-1"),
+    )
+}
+
+fn client() -> KewClient {
+  KewClient::builder()
+    .transport(transport())
+    .strict(true)
+    .build()
+    .unwrap()
+}
 
 #[tokio::test]
 async fn basic_search() {
-  let res = Powo::search("Poa Annua".into()).await.unwrap();
+  let res = client().powo().search("Poa Annua".into()).await.unwrap();
   let urn = Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap();
 
   assert_eq!(res.size(), 3);
@@ -17,7 +98,8 @@ async fn basic_search() {
 
 #[tokio::test]
 async fn advanced_name_search() {
-  let query = Powo::new()
+  let query = client()
+    .powo()
     .query(terms::Name::Genus, "Poa")
     .query(terms::Name::Species, "annua")
     .query(terms::Name::Author, "L.");
@@ -32,7 +114,8 @@ async fn advanced_name_search() {
 
 #[tokio::test]
 async fn advanced_characteristic_search() {
-  let res = Powo::new()
+  let res = client()
+    .powo()
     .query(terms::Characteristic::Flower, "yellow")
     .query(terms::Characteristic::Leaf, "alternate")
     .run()
@@ -44,7 +127,8 @@ async fn advanced_characteristic_search() {
 
 #[tokio::test]
 async fn advanced_geography_search() {
-  let res = Powo::new()
+  let res = client()
+    .powo()
     .query(terms::Geography::Distribution, "Africa")
     .run()
     .await
@@ -53,86 +137,739 @@ async fn advanced_geography_search() {
   assert!(res.size() > 0);
 }
 
+#[tokio::test]
+async fn lookup_many() {
+  let ids = ["650591-1", "1-1", "77170908-1"]
+    .map(|id| Urn::from_str(&format!("urn:lsid:ipni.org:names:{}", id)).unwrap());
+  let res = client()
+    .powo()
+    .lookup_many(
+      ids.clone(),
+      [LookupField::Distribution, LookupField::Descriptions],
+    )
+    .concurrency(2)
+    .stream()
+    .collect::<Vec<_>>()
+    .await;
+
+  assert_eq!(
+    res.iter().map(|(id, _)| id).collect::<Vec<_>>(),
+    ids.iter().collect::<Vec<_>>()
+  );
+
+  let lookup = res[0].1.as_ref().unwrap();
+
+  assert!(lookup.distribution.is_requested());
+  assert!(lookup.descriptions.is_requested());
+  // Only the first has a canned response with these fields.
+  assert!(res[1..].iter().all(|(_, res)| res.is_err()));
+}
+
+#[tokio::test]
+async fn region_geography_search() {
+  let res = client()
+    .powo()
+    .region(terms::Geography::Distribution, Region::L1("2".into()))
+    .unwrap()
+    .run()
+    .await
+    .unwrap();
+
+  assert!(res.size() > 0);
+}
+
+#[tokio::test]
+async fn region_names_are_not_escaped() {
+  let empty = r#"{"totalResults": 0, "results": []}"#;
+  let transport = MemoryTransport::new()
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=location:Asia-Temperate",
+      empty,
+    )
+    .json(
+      r#"/api/2/search?perPage=500&cursor=*&q=location:"Great Britain""#,
+      empty,
+    );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  // Codes read at runtime are owned.
+  let code = String::from("3");
+
+  for region in [Region::L1(code.into()), Region::L3("GRB".into())] {
+    client
+      .powo()
+      .region(terms::Geography::Distribution, region)
+      .unwrap()
+      .run()
+      .await
+      .unwrap();
+  }
+
+  assert!(matches!(
+    client
+      .powo()
+      .region(terms::Geography::Distribution, Region::L3("XXX".into())),
+    Err(Error::UnknownRegion(_))
+  ));
+}
+
 #[cfg(test)]
 mod poa {
   use super::*;
 
+  fn client() -> KewClient {
+    let transport = MemoryTransport::new()
+      .json(
+        "/api/2/taxon/urn:lsid:ipni.org:names:320035-2",
+        fixture!("lookup_320035-2"),
+      )
+      .json(
+        "/api/2/taxon/urn:lsid:ipni.org:names:320035-2?fields=distribution,descriptions",
+        fixture!("lookup_320035-2_distribution_descriptions"),
+      );
+
+    KewClient::builder()
+      .transport(transport)
+      .strict(true)
+      .build()
+      .unwrap()
+  }
+
   #[tokio::test]
   async fn lookup() {
-    let res = Powo::lookup(
-      Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap(),
-      None,
-    )
-    .await
-    .unwrap();
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap())
+      .send()
+      .await
+      .unwrap();
 
     assert_eq!(res.name, "Poa annua");
+    assert_eq!(res.rank, Rank::Species);
+    assert!(!res.rank.is_infraspecific());
+    assert!(
+      res
+        .classification
+        .windows(2)
+        .all(|pair| pair[0].rank > pair[1].rank)
+    );
+    assert!(
+      res
+        .synonyms
+        .unwrap()
+        .iter()
+        .all(|synonym| synonym.taxonomic_status.is_synonym())
+    );
   }
 
   #[tokio::test]
   async fn lookup_with_extra_fields() {
-    let res = Powo::lookup(
-      Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap(),
-      Some(vec!["distribution".into(), "descriptions".into()]),
-    )
-    .await
-    .unwrap();
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap())
+      .with(LookupField::Distribution)
+      .with(LookupField::Descriptions)
+      .send()
+      .await
+      .unwrap();
 
     assert_eq!(res.name, "Poa annua");
-    assert_eq!(res.distribution.unwrap().natives[0].name, "Afghanistan");
-    assert!(res.descriptions.is_some());
+    assert!(res.descriptions.get().is_some());
+
+    let distribution = res.distribution.get().unwrap();
+    let afghanistan = &distribution.natives[0];
+
+    assert_eq!(afghanistan.name, "Afghanistan");
+    assert_eq!(afghanistan.establishment, Some(Establishment::Native));
+    assert_eq!(afghanistan.area().unwrap().code, "AFG");
+    assert_eq!(afghanistan.region().unwrap().name, "Western Asia");
+    assert_eq!(afghanistan.continent().unwrap().name, "Asia-Temperate");
+    assert_eq!(distribution.all().count(), 30);
+
+    let continents = DistributionMap::roll_up(&distribution.introduced, Level::Continent)
+      .iter()
+      .map(|area| area.name)
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      continents,
+      ["Australasia", "Northern America", "Southern America"]
+    );
+    assert_eq!(res.location_areas().len(), 30);
+  }
+
+  #[tokio::test]
+  async fn descriptions() {
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:320035-2").unwrap())
+      .with(LookupField::Distribution)
+      .with(LookupField::Descriptions)
+      .send()
+      .await
+      .unwrap();
+
+    let leaf = res.descriptions_of(Characteristic::Leaf);
+
+    assert_eq!(leaf.len(), 1);
+    assert_eq!(
+      leaf[0].description.text(),
+      "Leaf-blades 1–5 mm wide, soft; apex cucullate, often transversely wrinkled."
+    );
+    assert_eq!(
+      leaf[0].description.markdown(),
+      "Leaf-blades 1–5 mm wide, soft; apex cucullate, often *transversely wrinkled*."
+    );
+    assert!(leaf[0].source.starts_with("Clayton, W.D."));
+    assert_eq!(leaf[0].as_taxon, "Poa annua L.");
+
+    let uses = res.descriptions_of(Characteristic::Use);
+
+    assert_eq!(uses[0].source, "Kew Species Profiles");
+    assert_eq!(
+      uses[0].description.markdown(),
+      "Used as a lawn and turf grass; [Kew](https://www.kew.org) notes it is a common weed of golf greens."
+    );
+
+    let general = res.descriptions_of(DescriptionKey::Other("general".into()));
+
+    assert_eq!(
+      general[0].description.text(),
+      "Poa annua is a small, tufted annual grass, and one of the most widespread plants on Earth."
+    );
+    assert!(res.descriptions_of(Characteristic::Fruit).is_empty());
   }
 }
 
+#[test]
+fn description_markup() {
+  let description = |html: &str| Description {
+    description: html.into(),
+    source: None,
+    extra: Default::default(),
+  };
+
+  let html = description(
+    "<p>Sepals <b>3</b> &times; 2&nbsp;mm;<br/>petals 5_6.</p><p>Lip &lt;= sepals &amp; &#x2264; 1 cm.</p>",
+  );
+
+  assert_eq!(
+    html.text(),
+    "Sepals 3 × 2 mm;\npetals 5_6.\n\nLip <= sepals & ≤ 1 cm."
+  );
+  assert_eq!(
+    html.markdown(),
+    "Sepals **3** × 2 mm;\npetals 5\\_6.\n\nLip <= sepals & ≤ 1 cm."
+  );
+  assert_eq!(description("a < b & c").text(), "a < b & c");
+
+  let measurements = description("<p>petals < 5 mm, sepals > 3 mm, <i>lip</i> <3 mm</p>");
+
+  assert_eq!(
+    measurements.text(),
+    "petals < 5 mm, sepals > 3 mm, lip <3 mm"
+  );
+  assert_eq!(
+    measurements.markdown(),
+    "petals < 5 mm, sepals > 3 mm, *lip* <3 mm"
+  );
+}
+
 #[cfg(test)]
 mod phalaenopsis {
   use super::*;
 
   #[tokio::test]
   async fn lookup() {
-    let res = Powo::lookup(
-      Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap(),
-      None,
-    )
-    .await
-    .unwrap();
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+      .send()
+      .await
+      .unwrap();
 
     assert_eq!(res.name, "Phalaenopsis schilleriana");
+    assert!(matches!(res.distribution, Section::NotRequested));
+    assert!(!res.descriptions.is_requested());
   }
 
   #[tokio::test]
   async fn lookup_2() {
-    let res = Powo::lookup(
-      Urn::from_str("urn:lsid:ipni.org:names:77170908-1").unwrap(),
-      None,
-    )
-    .await
-    .unwrap();
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:77170908-1").unwrap())
+      .send()
+      .await
+      .unwrap();
 
     assert_eq!(res.name, "Philodendron hederaceum var. hederaceum");
+    assert_eq!(res.rank, Rank::Variety);
+    assert!(res.rank.is_infraspecific());
+    assert_eq!(res.taxonomic_status, TaxonomicStatus::Accepted);
+    assert_eq!(res.nomenclatural_status, NomenclaturalStatus::Valid);
   }
 
   #[tokio::test]
   async fn lookup_with_extra_fields() {
-    let res = Powo::lookup(
-      Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap(),
-      Some(vec!["distribution".into(), "descriptions".into()]),
-    )
-    .await
-    .unwrap();
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+      .with(LookupField::Distribution)
+      .with(LookupField::Descriptions)
+      .send()
+      .await
+      .unwrap();
 
     assert_eq!(res.name, "Phalaenopsis schilleriana");
-    assert_eq!(res.distribution.unwrap().natives[0].name, "Philippines");
-    assert!(res.descriptions.is_some());
+    assert_eq!(
+      res.distribution.into_inner().unwrap().natives[0].name,
+      "Philippines"
+    );
+    assert!(res.descriptions.get().is_some());
+
+    // Fields are sent in a fixed order, so this is the same request.
+    let res = client()
+      .powo()
+      .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+      .with(LookupField::Descriptions)
+      .with(LookupField::Distribution)
+      .send()
+      .await
+      .unwrap();
+
+    assert!(res.distribution.is_requested());
+    assert!(res.descriptions.is_requested());
   }
 }
 
 #[tokio::test]
 async fn filters() {
-  let query = Powo::new().query(terms::Name::Family, "Poaceae");
+  let query = client().powo().query(terms::Name::Family, "Poaceae");
 
   let unfiltered = query.clone().run().await.unwrap();
   let filtered = query.clone().filter(Filters::Accepted).run().await.unwrap();
 
   assert!(filtered.size() < unfiltered.size());
 }
+
+#[tokio::test]
+async fn lookup_missing_section() {
+  let transport = MemoryTransport::new().json(
+    "/api/2/taxon/urn:lsid:ipni.org:names:650591-1?fields=distributionEnvelope,images,references",
+    fixture!("lookup_650591-1"),
+  );
+  let client = KewClient::builder()
+    .transport(transport)
+    .strict(true)
+    .build()
+    .unwrap();
+
+  let res = client
+    .powo()
+    .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+    .with(LookupField::References)
+    .with(LookupField::Images)
+    .with(LookupField::DistributionEnvelope)
+    .with(LookupField::Images)
+    .send()
+    .await
+    .unwrap();
+
+  assert!(matches!(res.images, Section::Missing));
+  assert!(matches!(res.distribution_envelope, Section::Missing));
+  assert!(matches!(res.references, Section::Missing));
+  assert!(matches!(res.distribution, Section::NotRequested));
+}
+
+#[tokio::test]
+async fn lookup_null_section() {
+  let body =
+    fixture!("lookup_650591-1").replacen('{', r#"{"images": null, "references": null,"#, 1);
+  let transport = MemoryTransport::new()
+    .json(
+      "/api/2/taxon/urn:lsid:ipni.org:names:650591-1",
+      body.clone(),
+    )
+    .json(
+      "/api/2/taxon/urn:lsid:ipni.org:names:650591-1?fields=images",
+      body,
+    );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+  let id = Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap();
+
+  // A null section that wasn't asked for is still `NotRequested`.
+  let res = client.powo().lookup(id.clone()).send().await.unwrap();
+
+  assert!(matches!(res.images, Section::NotRequested));
+  assert!(matches!(res.references, Section::NotRequested));
+
+  let res = client
+    .powo()
+    .lookup(id)
+    .with(LookupField::Images)
+    .send()
+    .await
+    .unwrap();
+
+  assert!(matches!(res.images, Section::Missing));
+  assert!(matches!(res.references, Section::NotRequested));
+}
+
+#[tokio::test]
+async fn decode_error_reports_path() {
+  let body = fixture!("lookup_650591-1").replace(r#""hybrid": false"#, r#""hybrid": "no""#);
+  let transport =
+    MemoryTransport::new().json("/api/2/taxon/urn:lsid:ipni.org:names:650591-1", body);
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  let err = client
+    .powo()
+    .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+    .send()
+    .await
+    .unwrap_err();
+
+  assert!(matches!(err, Error::Decode { path, raw_snippet, .. }
+    if path == "hybrid" && raw_snippet.contains(r#""hybrid": "no""#)));
+}
+
+fn client_with_new_field(builder: KewClientBuilder) -> KewClient {
+  let body = fixture!("lookup_650591-1").replacen(
+    r#""hybrid": false"#,
+    r#""hybrid": false, "conservationStatus": "EN""#,
+    1,
+  );
+  let transport =
+    MemoryTransport::new().json("/api/2/taxon/urn:lsid:ipni.org:names:650591-1", body);
+
+  builder.transport(transport).build().unwrap()
+}
+
+#[tokio::test]
+async fn unknown_fields_are_kept() {
+  let reported = Arc::new(Mutex::new(vec![]));
+  let hook = reported.clone();
+  let client = client_with_new_field(KewClient::builder().on_unknown_fields(move |unknown| {
+    hook.lock().unwrap().extend(unknown.fields.clone());
+  }));
+
+  let res = client
+    .powo()
+    .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+    .send()
+    .await
+    .unwrap();
+
+  assert_eq!(res.name, "Phalaenopsis schilleriana");
+  assert_eq!(res.extra["conservationStatus"], "EN");
+  assert_eq!(res.unknown_fields(), ["conservationStatus"]);
+  assert_eq!(*reported.lock().unwrap(), ["conservationStatus"]);
+}
+
+#[tokio::test]
+async fn unknown_fields_fail_when_strict() {
+  let client = client_with_new_field(KewClient::builder().strict(true));
+
+  let err = client
+    .powo()
+    .lookup(Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap())
+    .send()
+    .await
+    .unwrap_err();
+
+  assert!(matches!(err, Error::UnknownFields(unknown)
+    if unknown.fields == ["conservationStatus"]));
+}
+
+#[tokio::test]
+async fn record_and_replay() {
+  let path = std::env::temp_dir().join(format!("rustkew-{}-record.json", std::process::id()));
+  let id = Urn::from_str("urn:lsid:ipni.org:names:650591-1").unwrap();
+
+  let recording = CassetteTransport::record(&path, transport());
+  let client = KewClient::builder().transport(recording).build().unwrap();
+  let recorded = client.powo().lookup(id.clone()).send().await.unwrap();
+
+  let replaying = CassetteTransport::replay(&path).unwrap();
+  std::fs::remove_file(&path).unwrap();
+  let client = KewClient::builder().transport(replaying).build().unwrap();
+  let replayed = client.powo().lookup(id.clone()).send().await.unwrap();
+
+  assert_eq!(recorded.name, replayed.name);
+  assert!(client.powo().search("Poa Annua".into()).await.is_err());
+}
+
+fn page(cursor: &str, names: &[&str]) -> String {
+  let results = names
+    .iter()
+    .map(|name| {
+      format!(
+        r#"{{"accepted": true, "kingdom": "Plantae", "family": "Poaceae", "name": "{name}",
+          "rank": "Genus", "url": "/taxon/urn:lsid:ipni.org:names:{name}",
+          "fqId": "urn:lsid:ipni.org:names:{name}"}}"#
+      )
+    })
+    .collect::<Vec<_>>();
+
+  format!(
+    r#"{{"totalResults": 5, "cursor": "{cursor}", "results": [{}]}}"#,
+    results.join(",")
+  )
+}
+
+fn paged_client(per_page: usize) -> KewClient {
+  let transport = MemoryTransport::new()
+    .json(
+      &format!("/api/2/search?perPage={per_page}&cursor=*&q=family:Poaceae"),
+      page("a", &["Poa", "Festuca"]),
+    )
+    .json(
+      &format!("/api/2/search?perPage={per_page}&cursor=a&q=family:Poaceae"),
+      page("b", &["Lolium", "Bromus"]),
+    )
+    .json(
+      &format!("/api/2/search?perPage={per_page}&cursor=b&q=family:Poaceae"),
+      page("b", &["Avena"]),
+    );
+
+  KewClient::builder().transport(transport).build().unwrap()
+}
+
+#[tokio::test]
+async fn stream_follows_cursors() {
+  let names = paged_client(500)
+    .powo()
+    .query(terms::Name::Family, "Poaceae")
+    .stream()
+    .map_ok(|res| res.name)
+    .try_collect::<Vec<_>>()
+    .await
+    .unwrap();
+
+  assert_eq!(names, ["Poa", "Festuca", "Lolium", "Bromus", "Avena"]);
+}
+
+#[tokio::test]
+async fn stream_take() {
+  let transport = MemoryTransport::new()
+    .json(
+      "/api/2/search?perPage=500&cursor=*&q=family:Poaceae",
+      page("a", &["Poa", "Festuca"]),
+    )
+    .json(
+      "/api/2/search?perPage=500&cursor=a&q=family:Poaceae",
+      page("b", &[]),
+    );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+  let stream = client.powo().query(terms::Name::Family, "Poaceae").stream();
+
+  assert_eq!(stream.take(1).count().await, 1);
+
+  let stream = client.powo().query(terms::Name::Family, "Poaceae").stream();
+
+  assert_eq!(stream.count().await, 2);
+}
+
+#[tokio::test]
+async fn per_page_and_limit() {
+  let names = paged_client(2)
+    .powo()
+    .query(terms::Name::Family, "Poaceae")
+    .per_page(2)
+    .limit(3)
+    .stream()
+    .map_ok(|res| res.name)
+    .try_collect::<Vec<_>>()
+    .await
+    .unwrap();
+
+  assert_eq!(names, ["Poa", "Festuca", "Lolium"]);
+
+  let res = paged_client(1)
+    .powo()
+    .query(terms::Name::Family, "Poaceae")
+    .limit(1)
+    .run()
+    .await
+    .unwrap();
+
+  assert_eq!(res.results().len(), 1);
+  assert_eq!(res.cursor(), "a");
+}
+
+#[tokio::test]
+async fn search_uses_builder_options() {
+  let transport = MemoryTransport::new().json(
+    "/api/2/search?perPage=10&cursor=a&q=Poa&f=accepted_names",
+    page("b", &["Poa"]),
+  );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  let res = client
+    .powo()
+    .filter(Filters::Accepted)
+    .cursor("a".into())
+    .per_page(10)
+    .search("Poa".into())
+    .await
+    .unwrap();
+
+  assert_eq!(res.results()[0].name, "Poa");
+}
+
+#[tokio::test]
+async fn harvest_and_resume() {
+  let client = paged_client(500);
+  let query = client.powo().query(terms::Name::Family, "Poaceae");
+
+  let mut pages = pin!(query.harvest());
+  let first = pages.try_next().await.unwrap().unwrap();
+  let names = first.results.iter().map(|r| &r.name).collect::<Vec<_>>();
+
+  assert_eq!(names, ["Poa", "Festuca"]);
+  assert_eq!(first.state.cursor, "a");
+  assert_eq!(first.state.fetched, 2);
+
+  let path = std::env::temp_dir().join(format!("rustkew-{}-harvest.json", std::process::id()));
+  first.state.save(&path).unwrap();
+  let state = HarvestState::load(&path).unwrap();
+  std::fs::remove_file(&path).unwrap();
+
+  assert_eq!(state, first.state);
+
+  let pages = query
+    .resume(state.clone())
+    .unwrap()
+    .try_collect::<Vec<_>>()
+    .await
+    .unwrap();
+  let names = pages
+    .iter()
+    .flat_map(|p| p.results.iter().map(|r| &r.name))
+    .collect::<Vec<_>>();
+  let last = &pages.last().unwrap().state;
+
+  assert_eq!(names, ["Lolium", "Bromus", "Avena"]);
+  assert_eq!(last.fetched, 5);
+  assert!(last.finished);
+
+  let err = client
+    .powo()
+    .query(terms::Name::Family, "Orchidaceae")
+    .resume(state)
+    .err()
+    .unwrap();
+
+  assert!(matches!(err, Error::HarvestMismatch(_)));
+}
+
+#[test]
+fn unknown_statuses() {
+  let status: TaxonomicStatus = serde_json::from_str(r#""Provisionally_Accepted""#).unwrap();
+
+  assert_eq!(
+    status,
+    TaxonomicStatus::Other("Provisionally_Accepted".into())
+  );
+  assert_eq!(status.to_string(), "Provisionally_Accepted");
+  assert_eq!(
+    TaxonomicStatus::from("heterotypic synonym"),
+    TaxonomicStatus::HeterotypicSynonym
+  );
+  assert_eq!(
+    serde_json::to_string(&TaxonomicStatus::HomotypicSynonym).unwrap(),
+    r#""Homotypic_Synonym""#
+  );
+
+  let rank = Rank::from("Nothosubspecies");
+
+  assert_eq!(rank, Rank::Other("Nothosubspecies".into()));
+  assert_eq!(rank.partial_cmp(&Rank::Species), None);
+  assert!(!rank.is_infraspecific());
+  assert!(Rank::Kingdom < Rank::Form);
+}
+
+#[test]
+fn wgsrpd_table() {
+  let britain = wgsrpd::area("grb").unwrap();
+
+  assert_eq!(britain.name, "Great Britain");
+  assert_eq!(britain.level, Level::BotanicalCountry);
+  assert_eq!(wgsrpd::find("great britain"), Some(britain));
+  assert_eq!(britain.parent().unwrap().code, "10");
+  assert_eq!(britain.continent().unwrap().name, "Europe");
+  assert_eq!(britain.ancestor(Level::BotanicalCountry), Some(britain));
+
+  let northern_europe = wgsrpd::area("10").unwrap();
+
+  assert_eq!(northern_europe.children().count(), 9);
+  assert!(northern_europe.children().any(|area| area == britain));
+  assert_eq!(northern_europe.ancestor(Level::BotanicalCountry), None);
+  assert!(wgsrpd::area("XXX").is_none());
+
+  assert!(
+    wgsrpd::areas()
+      .iter()
+      .filter(|area| area.level == Level::BotanicalCountry)
+      .all(|area| area.continent().is_some())
+  );
+}
+
+#[test]
+fn wgsrpd_regions() {
+  assert_eq!(
+    Region::find("great britain"),
+    Some(Region::L3("GRB".into()))
+  );
+  assert_eq!(Region::find("GRB-oo"), Some(Region::L4("GRB-OO".into())));
+  assert_eq!(Region::find("Atlantis"), None);
+  assert_eq!(
+    String::try_from(Region::L3("GRB".into())).unwrap(),
+    "Great Britain"
+  );
+  assert!(matches!(
+    String::try_from(Region::L2("GRB".into())),
+    Err(Error::UnknownRegion(Region::L2(code))) if code == "GRB"
+  ));
+  assert!(Region::L3("XXX".into()).query().is_err());
+  assert!(
+    Region::L2("GRB".into())
+      .expanded_query(Level::BotanicalCountry)
+      .is_err()
+  );
+  assert_eq!(wgsrpd::area("GRB-OO").unwrap().level, Level::BasicUnit);
+
+  let northern_europe = Region::L2("10".into());
+
+  assert_eq!(
+    northern_europe.expand(Level::Region),
+    vec![northern_europe.clone()]
+  );
+  assert!(northern_europe.expand(Level::Continent).is_empty());
+  assert_eq!(northern_europe.expand(Level::BotanicalCountry).len(), 9);
+  assert!(
+    Region::L1("1".into())
+      .expand(Level::BotanicalCountry)
+      .contains(&Region::L3("GRB".into()))
+  );
+  assert_eq!(
+    Region::L3("IRE".into()).expand(Level::BasicUnit),
+    vec![Region::L4("IRE-IR".into()), Region::L4("IRE-NI".into())]
+  );
+
+  assert!(
+    wgsrpd::areas()
+      .iter()
+      .filter(|area| area.level == Level::BotanicalCountry)
+      .all(|area| area.children().next().is_some())
+  );
+
+  let query = Region::L3("IRE".into())
+    .expanded_query(Level::BasicUnit)
+    .unwrap();
+
+  assert_eq!(
+    query.format(),
+    r#"location:Ireland OR location:"Northern Ireland""#
+  );
+}
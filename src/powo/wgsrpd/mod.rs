@@ -0,0 +1,278 @@
+use std::{borrow::Cow, sync::OnceLock};
+
+use super::{Geography, PowoQuery};
+use crate::{Error, query::Query};
+
+/// A level of the World Geographical Scheme for Recording Plant
+/// Distributions (WGSRPD), also known as the TDWG regions.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Level {
+  /// Level 1, e.g. `1` Europe.
+  Continent,
+  /// Level 2, e.g. `10` Northern Europe.
+  Region,
+  /// Level 3, e.g. `GRB` Great Britain. POWO distributions are recorded at
+  /// this level.
+  BotanicalCountry,
+  /// Level 4, e.g. `GRB-OO` Great Britain. Botanical countries that aren't
+  /// subdivided have a single unit with the suffix `-OO`.
+  BasicUnit,
+}
+
+impl Level {
+  pub fn number(self) -> u8 {
+    match self {
+      Level::Continent => 1,
+      Level::Region => 2,
+      Level::BotanicalCountry => 3,
+      Level::BasicUnit => 4,
+    }
+  }
+
+  pub fn from_number(number: u8) -> Option<Level> {
+    Some(match number {
+      1 => Level::Continent,
+      2 => Level::Region,
+      3 => Level::BotanicalCountry,
+      4 => Level::BasicUnit,
+      _ => return None,
+    })
+  }
+}
+
+/// An entry in the bundled WGSRPD table.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Area {
+  pub level: Level,
+  pub code: &'static str,
+  pub name: &'static str,
+  parent: Option<&'static str>,
+}
+
+static AREAS: OnceLock<Vec<Area>> = OnceLock::new();
+
+/// Every area in the table, ordered by level and then as listed in the
+/// scheme.
+pub fn areas() -> &'static [Area] {
+  AREAS.get_or_init(|| {
+    include_str!("areas.tsv")
+      .lines()
+      .skip(1)
+      .filter(|line| !line.is_empty())
+      .map(|line| {
+        let mut columns = line.split('\t');
+        let mut next = || columns.next().unwrap_or_default();
+        let level = next()
+          .parse()
+          .ok()
+          .and_then(Level::from_number)
+          .expect("bad level in areas.tsv");
+
+        Area {
+          level,
+          code: next(),
+          name: next(),
+          parent: Some(next()).filter(|parent| !parent.is_empty()),
+        }
+      })
+      .collect()
+  })
+}
+
+/// Looks up an area by its code, e.g. `GRB` or `10`, ignoring case.
+pub fn area(code: &str) -> Option<&'static Area> {
+  let code = code.trim();
+  areas()
+    .iter()
+    .find(|area| area.code.eq_ignore_ascii_case(code))
+}
+
+/// Looks up an area by its name, e.g. `Great Britain`, ignoring case. Where
+/// areas at different levels share a name, the highest level wins, so
+/// `Great Britain` is `GRB` rather than `GRB-OO`.
+pub fn find(name: &str) -> Option<&'static Area> {
+  let name = name.trim();
+  areas()
+    .iter()
+    .find(|area| area.name.eq_ignore_ascii_case(name))
+}
+
+impl Area {
+  /// The area one level up, or `None` for a continent.
+  pub fn parent(&self) -> Option<&'static Area> {
+    area(self.parent?)
+  }
+
+  /// The area at `level` that contains this one, which is this area itself
+  /// if it is already at `level`.
+  pub fn ancestor(&'static self, level: Level) -> Option<&'static Area> {
+    let mut area = self;
+    while area.level > level {
+      area = area.parent()?;
+    }
+
+    (area.level == level).then_some(area)
+  }
+
+  pub fn region(&'static self) -> Option<&'static Area> {
+    self.ancestor(Level::Region)
+  }
+
+  pub fn continent(&'static self) -> Option<&'static Area> {
+    self.ancestor(Level::Continent)
+  }
+
+  /// The areas one level down.
+  pub fn children(&self) -> impl Iterator<Item = &'static Area> + use<'_> {
+    areas()
+      .iter()
+      .filter(move |area| area.parent == Some(self.code))
+  }
+}
+
+/// A WGSRPD area as a search term, e.g. `Region::L3("GRB".into())`. Codes
+/// can be borrowed or owned, so regions can be built from codes read at
+/// runtime. Regions render as the area's name, which is what POWO matches
+/// `Geography::Distribution` against. A code that isn't in the bundled table
+/// at the region's level is `Error::UnknownRegion` rather than a term POWO
+/// would never match.
+///
+/// ```
+/// # use rustkew::{Api, powo::{Geography, Powo, wgsrpd::Region}};
+/// # fn main() -> Result<(), rustkew::Error> {
+/// let query = Powo::new().region(Geography::Distribution, Region::L3("GRB".into()))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum Region {
+  L1(Cow<'static, str>),
+  L2(Cow<'static, str>),
+  L3(Cow<'static, str>),
+  L4(Cow<'static, str>),
+}
+
+impl Region {
+  pub fn new(level: Level, code: impl Into<Cow<'static, str>>) -> Region {
+    let code = code.into();
+    match level {
+      Level::Continent => Region::L1(code),
+      Level::Region => Region::L2(code),
+      Level::BotanicalCountry => Region::L3(code),
+      Level::BasicUnit => Region::L4(code),
+    }
+  }
+
+  /// The region for an area's name or code, ignoring case.
+  pub fn find(name_or_code: &str) -> Option<Region> {
+    find(name_or_code)
+      .or_else(|| area(name_or_code))
+      .map(Region::from)
+  }
+
+  pub fn level(&self) -> Level {
+    match self {
+      Region::L1(_) => Level::Continent,
+      Region::L2(_) => Level::Region,
+      Region::L3(_) => Level::BotanicalCountry,
+      Region::L4(_) => Level::BasicUnit,
+    }
+  }
+
+  pub fn code(&self) -> &str {
+    match self {
+      Region::L1(code) | Region::L2(code) | Region::L3(code) | Region::L4(code) => code,
+    }
+  }
+
+  /// This region's entry in the bundled table, or `None` if the code isn't
+  /// in the table at this region's level.
+  pub fn area(&self) -> Option<&'static Area> {
+    area(self.code()).filter(|area| area.level == self.level())
+  }
+
+  /// The regions at `level` within this one, in table order. Expanding to
+  /// this region's own level gives just this region, and expanding upwards
+  /// gives nothing.
+  pub fn expand(&self, level: Level) -> Vec<Region> {
+    let Some(area) = self.area() else {
+      return vec![];
+    };
+
+    let mut regions = vec![area];
+    while regions.first().is_some_and(|area| area.level < level) {
+      regions = regions.iter().flat_map(|area| area.children()).collect();
+    }
+
+    regions
+      .into_iter()
+      .filter(|area| area.level == level)
+      .map(Region::from)
+      .collect()
+  }
+
+  /// A `Geography::Distribution` term for this region.
+  pub fn query(&self) -> Result<Query<PowoQuery>, Error> {
+    self.term(Geography::Distribution)
+  }
+
+  /// A `geography` term for this region. Names with spaces are sent as a
+  /// phrase and the rest as they are, since escaping would turn e.g.
+  /// `Asia-Temperate` into a name POWO doesn't know.
+  pub(crate) fn term(&self, geography: Geography) -> Result<Query<PowoQuery>, Error> {
+    let name = String::try_from(self)?;
+
+    Ok(if name.contains(char::is_whitespace) {
+      Query::phrase(geography, name)
+    } else {
+      Query::raw(geography, name)
+    })
+  }
+
+  /// A query matching any of the regions at `level` within this one, e.g.
+  /// the botanical countries of a continent.
+  pub fn expanded_query(&self, level: Level) -> Result<Query<PowoQuery>, Error> {
+    self
+      .area()
+      .ok_or_else(|| Error::UnknownRegion(self.clone()))?;
+
+    self
+      .expand(level)
+      .iter()
+      .map(Region::query)
+      .collect::<Result<Vec<_>, _>>()
+      .map(Query::any)
+  }
+}
+
+impl From<&'static Area> for Region {
+  fn from(area: &'static Area) -> Self {
+    match area.level {
+      Level::Continent => Region::L1(area.code.into()),
+      Level::Region => Region::L2(area.code.into()),
+      Level::BotanicalCountry => Region::L3(area.code.into()),
+      Level::BasicUnit => Region::L4(area.code.into()),
+    }
+  }
+}
+
+impl TryFrom<&Region> for String {
+  type Error = Error;
+
+  /// The area's name.
+  fn try_from(region: &Region) -> Result<Self, Error> {
+    let area = region
+      .area()
+      .ok_or_else(|| Error::UnknownRegion(region.clone()))?;
+
+    Ok(area.name.to_owned())
+  }
+}
+
+impl TryFrom<Region> for String {
+  type Error = Error;
+
+  fn try_from(region: Region) -> Result<Self, Error> {
+    String::try_from(&region)
+  }
+}
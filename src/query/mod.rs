@@ -0,0 +1,249 @@
+use std::{
+  fmt,
+  ops::{BitAnd, BitOr, Not},
+};
+
+mod parse;
+pub use parse::ParseError;
+
+#[cfg(test)]
+mod test;
+
+pub trait ToKey: Sized {
+  fn to_key(&self) -> &'static str;
+  /// Every key, used to map query strings back to terms.
+  fn keys() -> impl Iterator<Item = Self>;
+
+  fn from_key(key: &str) -> Result<Self, ParseError> {
+    parse::parse_key(key)
+  }
+}
+
+/// A boolean search expression over `IpniQuery` or `PowoQuery` terms.
+///
+/// Expressions compose with `&`, `|` and `!` (or `and`, `or` and `not`), and
+/// nested operators are grouped with parentheses when rendered:
+///
+/// ```
+/// # use rustkew::{
+/// #   powo::{Name, PowoQuery},
+/// #   query::Query,
+/// # };
+/// let query: Query<PowoQuery> = Query::term(Name::Genus, "Poa")
+///   & (Query::term(Name::Species, "annua") | Query::phrase(Name::CommonName, "annual meadow grass"));
+///
+/// assert_eq!(
+///   query.to_string(),
+///   r#"genus:Poa AND (species:annua OR common name:"annual meadow grass")"#,
+/// );
+/// ```
+///
+/// The comma-separated `key:value` form sent by `Api::query` is what the Kew
+/// websites themselves send. `AND`, `OR`, `NOT`, parentheses, quoted phrases
+/// and backslash escapes are the Lucene query syntax of the Solr index behind
+/// both APIs. No recorded interaction in this crate exercises them yet, so
+/// that support is assumed rather than pinned by a test.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Query<K> {
+  /// A value matched against a specific field.
+  Term(K, Value),
+  /// A value matched against any field.
+  Text(Value),
+  And(Vec<Query<K>>),
+  Or(Vec<Query<K>>),
+  Not(Box<Query<K>>),
+}
+
+/// The right-hand side of a term.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Value {
+  /// A single word. Characters with special meaning, including whitespace,
+  /// commas and colons, are escaped.
+  Word(String),
+  /// An exact phrase, rendered in double quotes.
+  Phrase(String),
+  /// Passed through untouched, for wildcards and other server-side syntax.
+  Raw(String),
+}
+
+impl<K> Query<K> {
+  pub fn term(key: impl Into<K>, value: impl Into<String>) -> Self {
+    Query::Term(key.into(), Value::Word(value.into()))
+  }
+
+  pub fn phrase(key: impl Into<K>, value: impl Into<String>) -> Self {
+    Query::Term(key.into(), Value::Phrase(value.into()))
+  }
+
+  pub fn raw(key: impl Into<K>, value: impl Into<String>) -> Self {
+    Query::Term(key.into(), Value::Raw(value.into()))
+  }
+
+  pub fn text(value: impl Into<String>) -> Self {
+    Query::Text(Value::Word(value.into()))
+  }
+
+  pub fn all(queries: impl IntoIterator<Item = Query<K>>) -> Self {
+    Query::And(queries.into_iter().collect())
+  }
+
+  pub fn any(queries: impl IntoIterator<Item = Query<K>>) -> Self {
+    Query::Or(queries.into_iter().collect())
+  }
+
+  pub fn and(self, other: Query<K>) -> Self {
+    match self {
+      Query::And(mut queries) => {
+        queries.push(other);
+        Query::And(queries)
+      },
+      query => Query::And(vec![query, other]),
+    }
+  }
+
+  pub fn or(self, other: Query<K>) -> Self {
+    match self {
+      Query::Or(mut queries) => {
+        queries.push(other);
+        Query::Or(queries)
+      },
+      query => Query::Or(vec![query, other]),
+    }
+  }
+
+  pub(crate) fn is_compound(&self) -> bool {
+    match self {
+      Query::And(queries) | Query::Or(queries) => queries.len() > 1,
+      _ => false,
+    }
+  }
+}
+
+impl<K: ToKey> Query<K> {
+  pub fn format(&self) -> String {
+    self.to_string()
+  }
+
+  fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if self.is_compound() {
+      write!(f, "({})", self)
+    } else {
+      write!(f, "{}", self)
+    }
+  }
+
+  fn fmt_joined(queries: &[Query<K>], op: &str, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, query) in queries.iter().enumerate() {
+      if i > 0 {
+        write!(f, " {} ", op)?;
+      }
+      query.fmt_operand(f)?;
+    }
+
+    Ok(())
+  }
+}
+
+impl<K: ToKey> fmt::Display for Query<K> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Query::Term(key, value) => write!(f, "{}:{}", key.to_key(), value),
+      Query::Text(value) => write!(f, "{}", value),
+      Query::And(queries) => Self::fmt_joined(queries, "AND", f),
+      Query::Or(queries) => Self::fmt_joined(queries, "OR", f),
+      Query::Not(query) => {
+        write!(f, "NOT ")?;
+        query.fmt_operand(f)
+      },
+    }
+  }
+}
+
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Value::Word(word) => {
+        for c in word.chars() {
+          if is_special(c) {
+            write!(f, "\\")?;
+          }
+          write!(f, "{}", c)?;
+        }
+
+        Ok(())
+      },
+      Value::Phrase(phrase) => {
+        write!(f, "\"")?;
+        for c in phrase.chars() {
+          if c == '"' || c == '\\' {
+            write!(f, "\\")?;
+          }
+          write!(f, "{}", c)?;
+        }
+        write!(f, "\"")
+      },
+      Value::Raw(raw) => write!(f, "{}", raw),
+    }
+  }
+}
+
+pub(crate) fn is_special(c: char) -> bool {
+  c.is_whitespace()
+    || matches!(
+      c,
+      ','
+        | ':'
+        | '\\'
+        | '"'
+        | '('
+        | ')'
+        | '['
+        | ']'
+        | '{'
+        | '}'
+        | '+'
+        | '-'
+        | '!'
+        | '&'
+        | '|'
+        | '^'
+        | '~'
+        | '*'
+        | '?'
+        | '/'
+    )
+}
+
+impl<K> BitAnd for Query<K> {
+  type Output = Query<K>;
+
+  fn bitand(self, rhs: Query<K>) -> Query<K> {
+    self.and(rhs)
+  }
+}
+
+impl<K> BitOr for Query<K> {
+  type Output = Query<K>;
+
+  fn bitor(self, rhs: Query<K>) -> Query<K> {
+    self.or(rhs)
+  }
+}
+
+impl<K> Not for Query<K> {
+  type Output = Query<K>;
+
+  fn not(self) -> Query<K> {
+    Query::Not(Box::new(self))
+  }
+}
+
+impl<K> From<Vec<(K, String)>> for Query<K> {
+  fn from(terms: Vec<(K, String)>) -> Self {
+    Query::all(
+      terms
+        .into_iter()
+        .map(|(k, v)| Query::Term(k, Value::Word(v))),
+    )
+  }
+}
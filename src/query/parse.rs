@@ -0,0 +1,361 @@
+use std::{fmt, str::FromStr};
+
+use super::{Query, ToKey, Value};
+
+/// Why a query string couldn't be parsed. Positions are byte offsets into the
+/// input.
+#[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
+pub enum ParseError {
+  #[error("unknown key {key:?}{}", Suggestions(suggestions))]
+  UnknownKey {
+    key: String,
+    suggestions: Vec<&'static str>,
+  },
+  #[error("expected a value at {position}")]
+  MissingValue { position: usize },
+  #[error("unterminated phrase starting at {position}")]
+  UnterminatedPhrase { position: usize },
+  #[error("unexpected {found:?} at {position}")]
+  Unexpected { found: char, position: usize },
+  #[error("unexpected end of query")]
+  UnexpectedEnd,
+}
+
+struct Suggestions<'a>(&'a [&'static str]);
+
+impl fmt::Display for Suggestions<'_> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.0 {
+      [] => Ok(()),
+      [one] => write!(f, " (did you mean {:?}?)", one),
+      many => {
+        write!(f, " (did you mean one of ")?;
+        for (i, key) in many.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{:?}", key)?;
+        }
+        write!(f, "?)")
+      },
+    }
+  }
+}
+
+/// Looks up the key for `key`, ignoring case and surrounding whitespace.
+pub(crate) fn parse_key<K: ToKey>(key: &str) -> Result<K, ParseError> {
+  let key = key.trim();
+
+  K::keys()
+    .find(|k| k.to_key().eq_ignore_ascii_case(key))
+    .ok_or_else(|| unknown_key::<K>(key))
+}
+
+fn unknown_key<K: ToKey>(key: &str) -> ParseError {
+  let lower = key.to_lowercase();
+  let max = (lower.len() / 3).max(2);
+
+  let mut close = K::keys()
+    .map(|k| k.to_key())
+    .filter_map(|k| {
+      let distance = levenshtein(&lower, k);
+      let related = k.contains(lower.as_str()) || lower.contains(k);
+      (distance <= max || related).then_some((distance, k))
+    })
+    .collect::<Vec<_>>();
+  close.sort();
+  close.dedup();
+
+  ParseError::UnknownKey {
+    key: key.to_owned(),
+    suggestions: close.into_iter().take(3).map(|(_, k)| k).collect(),
+  }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+  let b = b.chars().collect::<Vec<_>>();
+  let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+  for (i, ca) in a.chars().enumerate() {
+    let mut prev = row[0];
+    row[0] = i + 1;
+
+    for (j, cb) in b.iter().enumerate() {
+      let cost = if ca == *cb { prev } else { prev + 1 };
+      prev = row[j + 1];
+      row[j + 1] = cost.min(row[j] + 1).min(row[j + 1] + 1);
+    }
+  }
+
+  row[b.len()]
+}
+
+/// Parses the syntax written by `Query::format`, as well as the plain
+/// `key:value,key:value` form typed into search boxes.
+///
+/// Commas, `AND` and plain juxtaposition all mean `AND`. Unquoted values may
+/// span several words, ending at the next operator or `key:`.
+impl<K: ToKey> FromStr for Query<K> {
+  type Err = ParseError;
+
+  fn from_str(s: &str) -> Result<Self, ParseError> {
+    let mut parser = Parser { input: s, pos: 0 };
+    let query = parser.parse_or()?;
+
+    parser.skip_ws();
+    match parser.peek() {
+      None => Ok(query),
+      Some(found) => Err(ParseError::Unexpected {
+        found,
+        position: parser.pos,
+      }),
+    }
+  }
+}
+
+struct Parser<'a> {
+  input: &'a str,
+  pos: usize,
+}
+
+impl Parser<'_> {
+  fn rest(&self) -> &str {
+    &self.input[self.pos..]
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.rest().chars().next()
+  }
+
+  fn bump(&mut self) -> Option<char> {
+    let c = self.peek()?;
+    self.pos += c.len_utf8();
+    Some(c)
+  }
+
+  fn skip_ws(&mut self) {
+    while self.peek().is_some_and(char::is_whitespace) {
+      self.bump();
+    }
+  }
+
+  fn eat_char(&mut self, c: char) -> bool {
+    self.skip_ws();
+    if self.peek() == Some(c) {
+      self.bump();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn eat_keyword(&mut self, keyword: &str) -> bool {
+    self.skip_ws();
+    if starts_with_keyword(self.rest(), keyword) {
+      self.pos += keyword.len();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn parse_or<K: ToKey>(&mut self) -> Result<Query<K>, ParseError> {
+    let mut queries = vec![self.parse_and()?];
+    while self.eat_keyword("OR") {
+      queries.push(self.parse_and()?);
+    }
+
+    Ok(collapse(queries, Query::Or))
+  }
+
+  fn parse_and<K: ToKey>(&mut self) -> Result<Query<K>, ParseError> {
+    let mut queries = vec![self.parse_unary()?];
+    loop {
+      if self.eat_char(',') || self.eat_keyword("AND") {
+        queries.push(self.parse_unary()?);
+        continue;
+      }
+
+      let rest = self.rest();
+      if rest.is_empty() || rest.starts_with(')') || starts_with_keyword(rest, "OR") {
+        break;
+      }
+
+      queries.push(self.parse_unary()?);
+    }
+
+    Ok(collapse(queries, Query::And))
+  }
+
+  fn parse_unary<K: ToKey>(&mut self) -> Result<Query<K>, ParseError> {
+    if self.eat_keyword("NOT") {
+      return Ok(Query::Not(Box::new(self.parse_unary()?)));
+    }
+
+    self.skip_ws();
+    match self.peek() {
+      None => Err(ParseError::UnexpectedEnd),
+      Some('(') => {
+        self.bump();
+        let query = self.parse_or()?;
+        if !self.eat_char(')') {
+          return Err(match self.peek() {
+            Some(found) => ParseError::Unexpected {
+              found,
+              position: self.pos,
+            },
+            None => ParseError::UnexpectedEnd,
+          });
+        }
+
+        Ok(query)
+      },
+      Some(found @ (')' | ',')) => Err(ParseError::Unexpected {
+        found,
+        position: self.pos,
+      }),
+      Some(_) => self.parse_term(),
+    }
+  }
+
+  fn parse_term<K: ToKey>(&mut self) -> Result<Query<K>, ParseError> {
+    match self.eat_key()? {
+      Some(key) => {
+        self.skip_ws();
+        Ok(Query::Term(key, self.parse_value::<K>()?))
+      },
+      None => Ok(Query::Text(self.parse_value::<K>()?)),
+    }
+  }
+
+  fn eat_key<K: ToKey>(&mut self) -> Result<Option<K>, ParseError> {
+    let rest = self.rest();
+
+    if let Some(key) = match_key::<K>(rest) {
+      self.pos += key.to_key().len() + 1;
+      return Ok(Some(key));
+    }
+
+    match rest.split_once(':') {
+      Some((candidate, _)) if is_key_like::<K>(candidate) => Err(unknown_key::<K>(candidate)),
+      _ => Ok(None),
+    }
+  }
+
+  fn parse_value<K: ToKey>(&mut self) -> Result<Value, ParseError> {
+    if self.peek() == Some('"') {
+      return self.parse_phrase();
+    }
+
+    let start = self.pos;
+    let mut value = String::new();
+    let mut raw = false;
+
+    while let Some(c) = self.peek() {
+      match c {
+        '\\' => {
+          self.bump();
+          value.push(self.bump().unwrap_or('\\'));
+        },
+        ',' | '(' | ')' | '"' => break,
+        c if c.is_whitespace() => {
+          if value_ends::<K>(self.rest().trim_start()) {
+            break;
+          }
+          value.push(c);
+          self.bump();
+        },
+        c => {
+          raw |= c == '*' || c == '?';
+          value.push(c);
+          self.bump();
+        },
+      }
+    }
+
+    if value.is_empty() {
+      return Err(ParseError::MissingValue { position: start });
+    }
+
+    if raw {
+      Ok(Value::Raw(self.input[start..self.pos].to_owned()))
+    } else {
+      Ok(Value::Word(value))
+    }
+  }
+
+  fn parse_phrase(&mut self) -> Result<Value, ParseError> {
+    let start = self.pos;
+    self.bump();
+
+    let mut phrase = String::new();
+    loop {
+      match self.bump() {
+        None => return Err(ParseError::UnterminatedPhrase { position: start }),
+        Some('"') => return Ok(Value::Phrase(phrase)),
+        Some('\\') => match self.bump() {
+          Some(c) => phrase.push(c),
+          None => return Err(ParseError::UnterminatedPhrase { position: start }),
+        },
+        Some(c) => phrase.push(c),
+      }
+    }
+  }
+}
+
+fn collapse<K>(mut queries: Vec<Query<K>>, op: fn(Vec<Query<K>>) -> Query<K>) -> Query<K> {
+  if queries.len() == 1 {
+    queries.pop().unwrap()
+  } else {
+    op(queries)
+  }
+}
+
+fn starts_with_keyword(s: &str, keyword: &str) -> bool {
+  s.strip_prefix(keyword).is_some_and(|rest| {
+    rest
+      .chars()
+      .next()
+      .is_none_or(|c| c.is_whitespace() || c == '(' || c == '"')
+  })
+}
+
+/// The longest key that `s` starts with, followed by a colon.
+fn match_key<K: ToKey>(s: &str) -> Option<K> {
+  K::keys()
+    .filter(|k| {
+      let key = k.to_key();
+      s.get(..key.len())
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case(key))
+        && s[key.len()..].starts_with(':')
+    })
+    .max_by_key(|k| k.to_key().len())
+}
+
+/// Whether `candidate` reads like a mistyped key rather than text that
+/// happens to precede a later `key:`.
+fn is_key_like<K: ToKey>(candidate: &str) -> bool {
+  let words = candidate.split(' ').collect::<Vec<_>>();
+
+  !candidate.is_empty()
+    && candidate
+      .chars()
+      .all(|c| c.is_alphanumeric() || c == ' ' || c == '_' || c == '-')
+    && !words
+      .iter()
+      .any(|w| matches!(*w, "" | "AND" | "OR" | "NOT"))
+    && !(1..words.len()).any(|i| match_key::<K>(&format!("{}:", words[i..].join(" "))).is_some())
+}
+
+/// Whether an unquoted value stops before `rest`, which follows whitespace.
+fn value_ends<K: ToKey>(rest: &str) -> bool {
+  rest.is_empty()
+    || rest.starts_with([',', '(', ')', '"'])
+    || ["AND", "OR", "NOT"]
+      .iter()
+      .any(|k| starts_with_keyword(rest, k))
+    || match_key::<K>(rest).is_some()
+    || rest
+      .split_once(':')
+      .is_some_and(|(candidate, _)| !candidate.contains(' ') && is_key_like::<K>(candidate))
+}
@@ -0,0 +1,274 @@
+use super::{ParseError, Query, Value};
+use crate::{
+  Api,
+  KewClient,
+  ipni::{IpniQuery, Name},
+  powo::{self, PowoQuery},
+  transport::MemoryTransport,
+};
+
+type IpniExpr = Query<IpniQuery>;
+
+#[test]
+fn term() {
+  let query = IpniExpr::term(Name::Genus, "Poa");
+
+  assert_eq!(query.format(), "genus:Poa");
+}
+
+#[test]
+fn term_escapes_special_characters() {
+  let query = IpniExpr::term(Name::Author, "Sm., J.E.");
+
+  assert_eq!(query.format(), r"name author:Sm.\,\ J.E.");
+
+  let query = IpniExpr::term(Name::Genus, "Poa:annua (L.)");
+
+  assert_eq!(query.format(), r"genus:Poa\:annua\ \(L.\)");
+}
+
+#[test]
+fn phrase() {
+  let query = IpniExpr::phrase(Name::FullName, r#"Poa "annua" L."#);
+
+  assert_eq!(query.format(), r#"full name:"Poa \"annua\" L.""#);
+}
+
+#[test]
+fn raw() {
+  let query = IpniExpr::raw(Name::Genus, "Po*");
+
+  assert_eq!(query.format(), "genus:Po*");
+}
+
+#[test]
+fn text() {
+  let query = IpniExpr::Text(Value::Phrase("Poa annua".into()));
+
+  assert_eq!(query.format(), r#""Poa annua""#);
+}
+
+#[test]
+fn and() {
+  let query = IpniExpr::term(Name::Genus, "Poa")
+    & IpniExpr::term(Name::Species, "annua")
+    & IpniExpr::term(Name::Author, "L.");
+
+  assert_eq!(
+    query.format(),
+    "genus:Poa AND species:annua AND name author:L."
+  );
+}
+
+#[test]
+fn or() {
+  let query = IpniExpr::any([
+    IpniExpr::term(Name::Species, "annua"),
+    IpniExpr::term(Name::Species, "pratensis"),
+  ]);
+
+  assert_eq!(query.format(), "species:annua OR species:pratensis");
+}
+
+#[test]
+fn not() {
+  let query = IpniExpr::term(Name::Genus, "Poa") & !IpniExpr::term(Name::Species, "annua");
+
+  assert_eq!(query.format(), "genus:Poa AND NOT species:annua");
+
+  let query = !(IpniExpr::term(Name::Genus, "Poa") | IpniExpr::term(Name::Genus, "Festuca"));
+
+  assert_eq!(query.format(), "NOT (genus:Poa OR genus:Festuca)");
+}
+
+#[test]
+fn nested_groups() {
+  let query = IpniExpr::term(Name::Family, "Poaceae")
+    & (IpniExpr::term(Name::Genus, "Poa")
+      | (IpniExpr::term(Name::Genus, "Festuca") & IpniExpr::term(Name::Species, "rubra")));
+
+  assert_eq!(
+    query.format(),
+    "family:Poaceae AND (genus:Poa OR (genus:Festuca AND species:rubra))"
+  );
+
+  let single = IpniExpr::all([IpniExpr::term(Name::Genus, "Poa")]) & IpniExpr::text("annua");
+
+  assert_eq!(single.format(), "genus:Poa AND annua");
+}
+
+#[tokio::test]
+async fn expr_combines_with_query() {
+  let transport = MemoryTransport::new().json(
+    "/api/1/search?perPage=500&cursor=*&q=family:Poaceae,(genus:Poa OR genus:Festuca)",
+    r#"{"totalResults": 0, "results": []}"#,
+  );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  let res = client
+    .ipni()
+    .query(Name::Family, "Poaceae")
+    .expr(IpniExpr::term(Name::Genus, "Poa") | IpniExpr::term(Name::Genus, "Festuca"))
+    .run()
+    .await
+    .unwrap();
+
+  assert_eq!(res.size(), 0);
+}
+
+#[tokio::test]
+async fn query_values_are_sent_raw() {
+  let transport = MemoryTransport::new()
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=full name:Poa annua,genus:Po*",
+      r#"{"totalResults": 0, "results": []}"#,
+    )
+    .json(
+      "/api/1/search?perPage=500&cursor=*&q=genus:Po*,species:annua",
+      r#"{"totalResults": 0, "results": []}"#,
+    );
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  client
+    .ipni()
+    .query(Name::FullName, "Poa annua")
+    .query(Name::Genus, "Po*")
+    .run()
+    .await
+    .unwrap();
+
+  // Adding an expression doesn't change how the terms are sent.
+  client
+    .ipni()
+    .query(Name::Genus, "Po*")
+    .expr(IpniExpr::term(Name::Species, "annua"))
+    .run()
+    .await
+    .unwrap();
+}
+
+#[test]
+fn parse_search_box() {
+  let query = "genus:Poa,species:annua".parse::<IpniExpr>().unwrap();
+
+  assert_eq!(
+    query,
+    IpniExpr::term(Name::Genus, "Poa") & IpniExpr::term(Name::Species, "annua")
+  );
+
+  let query = "Genus: Poa, name author:L. f. species:annua"
+    .parse::<IpniExpr>()
+    .unwrap();
+
+  assert_eq!(
+    query,
+    IpniExpr::all([
+      IpniExpr::term(Name::Genus, "Poa"),
+      IpniExpr::term(Name::Author, "L. f."),
+      IpniExpr::term(Name::Species, "annua"),
+    ])
+  );
+
+  let query = "Poa annua".parse::<Query<PowoQuery>>().unwrap();
+
+  assert_eq!(query, Query::text("Poa annua"));
+
+  let query = "common name:meadow grass"
+    .parse::<Query<PowoQuery>>()
+    .unwrap();
+
+  assert_eq!(query, Query::term(powo::Name::CommonName, "meadow grass"));
+}
+
+#[test]
+fn parse_operators() {
+  let query = r#"family:Poaceae AND NOT (genus:Poa OR full name:"Festuca rubra") Po*"#
+    .parse::<IpniExpr>()
+    .unwrap();
+
+  assert_eq!(
+    query,
+    IpniExpr::all([
+      IpniExpr::term(Name::Family, "Poaceae"),
+      !(IpniExpr::term(Name::Genus, "Poa") | IpniExpr::phrase(Name::FullName, "Festuca rubra")),
+      IpniExpr::Text(Value::Raw("Po*".into())),
+    ])
+  );
+}
+
+#[test]
+fn parse_round_trip() {
+  let queries = [
+    IpniExpr::term(Name::Author, "Sm., J.E.") & IpniExpr::raw(Name::Genus, "Po*"),
+    IpniExpr::term(Name::Family, "Poaceae")
+      & (IpniExpr::term(Name::Genus, "Poa")
+        | (IpniExpr::phrase(Name::FullName, r#"Festuca "rubra""#)
+          & !IpniExpr::term(Name::Rank, "spec.")))
+      & IpniExpr::text("annua"),
+    !IpniExpr::term(Name::PublishedIn, "Sp. Pl. (1753)"),
+  ];
+
+  for query in queries {
+    assert_eq!(query.format().parse::<IpniExpr>().unwrap(), query);
+  }
+}
+
+#[test]
+fn parse_unknown_key() {
+  let err = "genus:Poa,speces:annua".parse::<IpniExpr>().unwrap_err();
+
+  assert_eq!(
+    err,
+    ParseError::UnknownKey {
+      key: "speces".into(),
+      suggestions: vec!["species"],
+    }
+  );
+  assert_eq!(
+    err.to_string(),
+    r#"unknown key "speces" (did you mean "species"?)"#
+  );
+
+  let err = "author:L.".parse::<IpniExpr>().unwrap_err();
+
+  assert_eq!(
+    err.to_string(),
+    r#"unknown key "author" (did you mean one of "author std", "tl2 author", "author name"?)"#
+  );
+
+  let err = "genus:Poa speces:annua".parse::<IpniExpr>().unwrap_err();
+
+  assert!(matches!(err, ParseError::UnknownKey { key, .. } if key == "speces"));
+}
+
+#[test]
+fn parse_errors() {
+  assert_eq!(
+    "genus:".parse::<IpniExpr>().unwrap_err(),
+    ParseError::MissingValue { position: 6 }
+  );
+  assert_eq!(
+    r#"full name:"Poa"#.parse::<IpniExpr>().unwrap_err(),
+    ParseError::UnterminatedPhrase { position: 10 }
+  );
+  assert_eq!(
+    "(genus:Poa".parse::<IpniExpr>().unwrap_err(),
+    ParseError::UnexpectedEnd
+  );
+  assert_eq!(
+    "genus:Poa)".parse::<IpniExpr>().unwrap_err(),
+    ParseError::Unexpected {
+      found: ')',
+      position: 9
+    }
+  );
+}
+
+#[test]
+fn parse_key() {
+  assert_eq!(
+    "Common Name".parse::<PowoQuery>().unwrap(),
+    PowoQuery::Name(powo::Name::CommonName)
+  );
+  assert!("flowers".parse::<PowoQuery>().is_err());
+}
@@ -0,0 +1,88 @@
+use std::{
+  collections::HashMap,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
+
+use url::Url;
+
+#[cfg(test)]
+mod test;
+
+/// How fast `KewClient` sends requests to each host, as a token bucket.
+///
+/// Up to `burst` requests go out at once, after which they are spaced out to
+/// average `per_second`. IPNI and POWO have separate budgets, shared by every
+/// call made through the same client. Responses served from the cache don't
+/// count.
+#[derive(Debug, Clone)]
+pub struct RateLimit {
+  pub per_second: f64,
+  pub burst: u32,
+}
+
+impl Default for RateLimit {
+  fn default() -> Self {
+    Self {
+      per_second: 10.0,
+      burst: 10,
+    }
+  }
+}
+
+#[derive(Debug)]
+pub(crate) struct Limiter {
+  limit: RateLimit,
+  buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+  /// Goes negative when requests are waiting for tokens that haven't been
+  /// added yet.
+  tokens: f64,
+  updated: Instant,
+}
+
+impl Limiter {
+  pub(crate) fn new(limit: RateLimit) -> Self {
+    Self {
+      limit,
+      buckets: Mutex::default(),
+    }
+  }
+
+  /// Waits until a request to `url`'s host is allowed.
+  pub(crate) async fn acquire(&self, url: &Url) {
+    let wait = self.reserve(url.host_str().unwrap_or_default());
+
+    if !wait.is_zero() {
+      tokio::time::sleep(wait).await;
+    }
+  }
+
+  /// Takes a token for `host`, returning how long until it is due. Tokens are
+  /// taken up front so that waiting requests go out in the order they asked.
+  fn reserve(&self, host: &str) -> Duration {
+    let burst = self.limit.burst.max(1) as f64;
+    let now = Instant::now();
+
+    let mut buckets = self.buckets.lock().unwrap();
+    let bucket = buckets.entry(host.to_owned()).or_insert(Bucket {
+      tokens: burst,
+      updated: now,
+    });
+
+    let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+    bucket.tokens = (bucket.tokens + elapsed * self.limit.per_second).min(burst);
+    bucket.updated = now;
+    bucket.tokens -= 1.0;
+
+    if bucket.tokens >= 0.0 {
+      Duration::ZERO
+    } else {
+      // Only a vanishingly small rate can overflow a `Duration`.
+      Duration::try_from_secs_f64(-bucket.tokens / self.limit.per_second).unwrap_or(Duration::MAX)
+    }
+  }
+}
@@ -0,0 +1,66 @@
+use std::{
+  str::FromStr,
+  time::{Duration, Instant},
+};
+
+use urn::Urn;
+
+use crate::{Error, KewClient, RateLimit, transport::MemoryTransport};
+
+fn transport() -> MemoryTransport {
+  MemoryTransport::new()
+    .json(
+      "/api/1/a/12653-1",
+      include_str!("../../fixtures/ipni/lookup_author_12653-1.json"),
+    )
+    .json(
+      "/api/2/taxon/urn:lsid:ipni.org:names:77170908-1",
+      include_str!("../../fixtures/powo/lookup_77170908-1.json"),
+    )
+}
+
+#[tokio::test]
+async fn rate_limit_per_host() {
+  let client = KewClient::builder()
+    .transport(transport())
+    .rate_limit(RateLimit {
+      per_second: 10.0,
+      burst: 2,
+    })
+    .build()
+    .unwrap();
+  let id = "12653-1".parse().unwrap();
+
+  // Two go out at once, then one every 100ms.
+  let start = Instant::now();
+  for _ in 0..3 {
+    client.ipni().lookup_author(&id).await.unwrap();
+  }
+
+  assert!(start.elapsed() >= Duration::from_millis(95));
+
+  // POWO has a budget of its own, so doesn't wait for the next IPNI token.
+  let start = Instant::now();
+  client
+    .powo()
+    .lookup(Urn::from_str("urn:lsid:ipni.org:names:77170908-1").unwrap())
+    .send()
+    .await
+    .unwrap();
+
+  assert!(start.elapsed() < Duration::from_millis(50));
+}
+
+#[test]
+fn rate_limit_must_be_positive() {
+  for per_second in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+    let res = KewClient::builder()
+      .rate_limit(RateLimit {
+        per_second,
+        burst: 1,
+      })
+      .build();
+
+    assert!(matches!(res, Err(Error::InvalidRateLimit { .. })));
+  }
+}
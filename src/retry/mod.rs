@@ -0,0 +1,103 @@
+use std::{
+  hash::{BuildHasher, Hasher, RandomState},
+  time::{Duration, SystemTime},
+};
+
+use reqwest::{
+  StatusCode,
+  header::{HeaderMap, RETRY_AFTER},
+};
+
+#[cfg(test)]
+mod test;
+
+/// How `KewClient` retries requests that were rate limited or hit a
+/// temporarily unavailable server.
+///
+/// Delays grow exponentially from `base_delay`, capped at `max_delay`, with
+/// jitter applied. A `Retry-After` header from the server takes precedence,
+/// unless it asks for a longer wait than `max_delay`, in which case the
+/// request fails straight away with the server's `retry_after`.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+  pub max_retries: u32,
+  pub base_delay: Duration,
+  pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    Self {
+      max_retries: 5,
+      base_delay: Duration::from_millis(500),
+      max_delay: Duration::from_secs(30),
+    }
+  }
+}
+
+impl RetryPolicy {
+  pub fn none() -> Self {
+    Self {
+      max_retries: 0,
+      ..Self::default()
+    }
+  }
+
+  pub(crate) fn should_retry(&self, status: StatusCode, attempt: u32) -> bool {
+    attempt < self.max_retries && is_retryable(status)
+  }
+
+  /// How long to wait before the next attempt, or `None` if the server asked
+  /// for longer than `max_delay`.
+  pub(crate) fn delay(&self, attempt: u32, headers: &HeaderMap) -> Option<Duration> {
+    match retry_after(headers) {
+      Some(wait) if wait > self.max_delay => None,
+      Some(wait) => Some(wait),
+      None => Some(self.backoff(attempt)),
+    }
+  }
+
+  fn backoff(&self, attempt: u32) -> Duration {
+    let exp = self
+      .base_delay
+      .saturating_mul(2u32.saturating_pow(attempt))
+      .min(self.max_delay);
+
+    // Equal jitter: wait at least half the backoff, plus a random share of
+    // the other half.
+    exp / 2 + (exp / 2).mul_f64(jitter())
+  }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+  matches!(
+    status,
+    StatusCode::TOO_MANY_REQUESTS
+      | StatusCode::BAD_GATEWAY
+      | StatusCode::SERVICE_UNAVAILABLE
+      | StatusCode::GATEWAY_TIMEOUT
+  )
+}
+
+/// Parses `Retry-After` as either a number of seconds or an HTTP date.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+  let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+  if let Ok(secs) = value.parse::<u64>() {
+    return Some(Duration::from_secs(secs));
+  }
+
+  let date = httpdate::parse_http_date(value).ok()?;
+  Some(
+    date
+      .duration_since(SystemTime::now())
+      .unwrap_or(Duration::ZERO),
+  )
+}
+
+/// A value in `[0, 1)`, good enough to spread out retries without pulling in
+/// a random number generator.
+fn jitter() -> f64 {
+  let bits = RandomState::new().build_hasher().finish();
+  (bits >> 11) as f64 / (1u64 << 53) as f64
+}
@@ -0,0 +1,98 @@
+use std::time::{Duration, SystemTime};
+
+use reqwest::{
+  StatusCode,
+  header::{HeaderMap, RETRY_AFTER},
+};
+
+use super::{RetryPolicy, is_retryable, retry_after};
+use crate::{
+  Error,
+  KewClient,
+  transport::{HttpResponse, MemoryTransport},
+};
+
+fn headers(retry_after: &str) -> HeaderMap {
+  let mut headers = HeaderMap::new();
+  headers.insert(RETRY_AFTER, retry_after.parse().unwrap());
+
+  headers
+}
+
+#[test]
+fn backoff() {
+  let policy = RetryPolicy {
+    max_retries: 5,
+    base_delay: Duration::from_millis(100),
+    max_delay: Duration::from_secs(1),
+  };
+
+  for _ in 0..20 {
+    let first = policy.backoff(0);
+    assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+    let third = policy.backoff(2);
+    assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+
+    let capped = policy.backoff(30);
+    assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_secs(1));
+  }
+}
+
+#[test]
+fn retryable_statuses() {
+  for status in [429, 502, 503, 504] {
+    assert!(is_retryable(StatusCode::from_u16(status).unwrap()));
+  }
+  for status in [400, 404, 500] {
+    assert!(!is_retryable(StatusCode::from_u16(status).unwrap()));
+  }
+
+  assert!(!RetryPolicy::none().should_retry(StatusCode::TOO_MANY_REQUESTS, 0));
+}
+
+#[test]
+fn retry_after_header() {
+  assert_eq!(retry_after(&headers("120")), Some(Duration::from_secs(120)));
+  assert_eq!(retry_after(&headers(" 0 ")), Some(Duration::ZERO));
+  assert_eq!(retry_after(&headers("soon")), None);
+  assert_eq!(retry_after(&HeaderMap::new()), None);
+
+  let later = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(60));
+  let wait = retry_after(&headers(&later)).unwrap();
+  assert!(wait > Duration::from_secs(55) && wait <= Duration::from_secs(60));
+
+  let earlier = httpdate::fmt_http_date(SystemTime::now() - Duration::from_secs(60));
+  assert_eq!(retry_after(&headers(&earlier)), Some(Duration::ZERO));
+}
+
+#[test]
+fn retry_after_is_capped() {
+  let policy = RetryPolicy::default();
+
+  assert_eq!(policy.delay(0, &headers("1")), Some(Duration::from_secs(1)));
+  assert_eq!(policy.delay(0, &headers("86400")), None);
+
+  let tomorrow = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(86400));
+  assert_eq!(policy.delay(0, &headers(&tomorrow)), None);
+}
+
+#[tokio::test]
+async fn long_retry_after_fails_fast() {
+  let mut limited = HttpResponse::new(StatusCode::TOO_MANY_REQUESTS, "");
+  limited.headers = headers("86400");
+
+  let transport = MemoryTransport::new().respond("/api/1/a/12653-1", limited);
+  let client = KewClient::builder().transport(transport).build().unwrap();
+
+  let err = client
+    .ipni()
+    .lookup_author(&"12653-1".parse().unwrap())
+    .await
+    .unwrap_err();
+
+  assert!(matches!(
+    err,
+    Error::RateLimited { retry_after: Some(wait) } if wait == Duration::from_secs(86400)
+  ));
+}
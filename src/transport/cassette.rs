@@ -0,0 +1,202 @@
+use std::{
+  collections::BTreeMap,
+  fs,
+  path::{Path, PathBuf},
+  sync::{Arc, Mutex},
+};
+
+use futures::future::BoxFuture;
+use reqwest::{
+  StatusCode,
+  header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::{HttpRequest, HttpResponse, MemoryTransport, Transport, request_key};
+use crate::Error;
+
+/// Request/response pairs saved to disk, in the order they were made.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct Cassette {
+  pub interactions: Vec<Interaction>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Interaction {
+  pub request: RecordedRequest,
+  pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecordedRequest {
+  pub method: String,
+  pub url: Url,
+}
+
+/// A response as stored in a cassette. JSON bodies are kept as JSON so the
+/// file stays readable, anything else as text.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecordedResponse {
+  pub status: u16,
+  #[serde(default)]
+  pub headers: BTreeMap<String, String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub json: Option<serde_json::Value>,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
+  pub body: String,
+}
+
+impl Cassette {
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+    let file = fs::read(path)?;
+    serde_json::from_slice(&file).map_err(Error::Cassette)
+  }
+
+  pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+    let file = serde_json::to_vec_pretty(self).map_err(Error::Cassette)?;
+    Ok(fs::write(path, file)?)
+  }
+}
+
+impl From<&HttpResponse> for RecordedResponse {
+  fn from(res: &HttpResponse) -> Self {
+    let headers = res
+      .headers
+      .iter()
+      .filter_map(|(k, v)| Some((k.to_string(), v.to_str().ok()?.to_owned())))
+      .collect();
+
+    match serde_json::from_slice(&res.body) {
+      Ok(json) => Self {
+        status: res.status.as_u16(),
+        headers,
+        json: Some(json),
+        body: String::new(),
+      },
+      Err(_) => Self {
+        status: res.status.as_u16(),
+        headers,
+        json: None,
+        body: String::from_utf8_lossy(&res.body).into_owned(),
+      },
+    }
+  }
+}
+
+impl TryFrom<&RecordedResponse> for HttpResponse {
+  type Error = Error;
+
+  fn try_from(res: &RecordedResponse) -> Result<Self, Error> {
+    let status = StatusCode::from_u16(res.status)
+      .map_err(|_| Error::Transport(format!("invalid status {}", res.status).into()))?;
+
+    let mut headers = HeaderMap::new();
+    for (k, v) in &res.headers {
+      let (Ok(k), Ok(v)) = (HeaderName::try_from(k), HeaderValue::try_from(v)) else {
+        continue;
+      };
+      headers.insert(k, v);
+    }
+
+    let body = match &res.json {
+      Some(json) => serde_json::to_vec(json).map_err(Error::Cassette)?,
+      None => res.body.clone().into_bytes(),
+    };
+
+    Ok(HttpResponse {
+      status,
+      headers,
+      body,
+    })
+  }
+}
+
+/// Records traffic to a cassette file, or replays a recorded cassette without
+/// touching the network.
+///
+/// ```no_run
+/// # use rustkew::{KewClient, transport::{CassetteTransport, ReqwestTransport}};
+/// # fn main() -> Result<(), rustkew::Error> {
+/// let recording = CassetteTransport::record("poa.json", ReqwestTransport::default());
+/// let client = KewClient::builder().transport(recording).build()?;
+///
+/// let replaying = CassetteTransport::replay("poa.json")?;
+/// let client = KewClient::builder().transport(replaying).build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CassetteTransport {
+  mode: Mode,
+}
+
+#[derive(Debug)]
+enum Mode {
+  Record {
+    inner: Arc<dyn Transport>,
+    path: PathBuf,
+    cassette: Mutex<Cassette>,
+  },
+  Replay(MemoryTransport),
+}
+
+impl CassetteTransport {
+  /// Sends requests through `inner`, saving each interaction to `path` as it
+  /// completes. Any existing cassette at `path` is replaced.
+  pub fn record(path: impl Into<PathBuf>, inner: impl Transport + 'static) -> Self {
+    Self {
+      mode: Mode::Record {
+        inner: Arc::new(inner),
+        path: path.into(),
+        cassette: Mutex::default(),
+      },
+    }
+  }
+
+  /// Serves the interactions saved in `path`. Requests are matched on their
+  /// path and query, and repeated requests are answered in recorded order.
+  pub fn replay(path: impl AsRef<Path>) -> Result<Self, Error> {
+    Self::from_cassette(&Cassette::load(path)?)
+  }
+
+  pub fn from_cassette(cassette: &Cassette) -> Result<Self, Error> {
+    let transport = MemoryTransport::new();
+    for interaction in &cassette.interactions {
+      transport.push(
+        &request_key(&interaction.request.url),
+        (&interaction.response).try_into()?,
+      );
+    }
+
+    Ok(Self {
+      mode: Mode::Replay(transport),
+    })
+  }
+}
+
+impl Transport for CassetteTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    match &self.mode {
+      Mode::Replay(transport) => transport.send(request),
+      Mode::Record {
+        inner,
+        path,
+        cassette,
+      } => Box::pin(async move {
+        let method = request.method.to_string();
+        let url = request.url.clone();
+        let res = inner.send(request).await?;
+
+        let mut cassette = cassette.lock().unwrap();
+        cassette.interactions.push(Interaction {
+          request: RecordedRequest { method, url },
+          response: (&res).into(),
+        });
+        cassette.save(path)?;
+
+        Ok(res)
+      }),
+    }
+  }
+}
@@ -0,0 +1,35 @@
+use futures::future::BoxFuture;
+
+use super::{HttpRequest, HttpResponse, Transport};
+use crate::Error;
+
+/// The default transport, sending requests over a pooled `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+  client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+  pub fn new(client: reqwest::Client) -> Self {
+    Self { client }
+  }
+}
+
+impl Transport for ReqwestTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    Box::pin(async move {
+      let res = self
+        .client
+        .request(request.method, request.url)
+        .headers(request.headers)
+        .send()
+        .await?;
+
+      Ok(HttpResponse {
+        status: res.status(),
+        headers: res.headers().clone(),
+        body: res.bytes().await?.to_vec(),
+      })
+    })
+  }
+}
@@ -0,0 +1,91 @@
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::Mutex,
+};
+
+use futures::future::BoxFuture;
+use url::Url;
+
+use super::{HttpRequest, HttpResponse, Transport, request_key};
+use crate::Error;
+
+/// Serves canned responses without touching the network.
+///
+/// Responses are keyed by path and query, so the same fixtures work whatever
+/// base URL the client is configured with:
+///
+/// ```
+/// # use rustkew::transport::MemoryTransport;
+/// let transport = MemoryTransport::new().json(
+///   "/api/1/n/320035-2",
+///   r#"{"id": "320035-2", "name": "Poa annua"}"#,
+/// );
+/// ```
+///
+/// Several responses for the same key are served in order, with the last one
+/// repeated once the others are used up.
+#[derive(Debug, Default)]
+pub struct MemoryTransport {
+  responses: Mutex<HashMap<String, VecDeque<HttpResponse>>>,
+  requests: Mutex<Vec<Url>>,
+}
+
+impl MemoryTransport {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a `200 OK` response for `path_and_query`.
+  pub fn json(self, path_and_query: &str, body: impl Into<String>) -> Self {
+    self.respond(path_and_query, HttpResponse::ok(body.into()))
+  }
+
+  pub fn respond(self, path_and_query: &str, response: HttpResponse) -> Self {
+    self.push(path_and_query, response);
+
+    self
+  }
+
+  pub fn push(&self, path_and_query: &str, response: HttpResponse) {
+    let key = parse_key(path_and_query);
+
+    self
+      .responses
+      .lock()
+      .unwrap()
+      .entry(key)
+      .or_default()
+      .push_back(response);
+  }
+
+  /// Every URL requested so far, in order.
+  pub fn requests(&self) -> Vec<Url> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+impl Transport for MemoryTransport {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>> {
+    self.requests.lock().unwrap().push(request.url.clone());
+
+    let key = request_key(&request.url);
+    let mut responses = self.responses.lock().unwrap();
+    let res = match responses.get_mut(&key) {
+      Some(queue) if queue.len() > 1 => queue.pop_front(),
+      Some(queue) => queue.front().cloned(),
+      None => None,
+    };
+
+    Box::pin(async move {
+      res.ok_or_else(|| Error::Transport(format!("no canned response for {}", key).into()))
+    })
+  }
+}
+
+fn parse_key(path_and_query: &str) -> String {
+  let path_and_query = format!("/{}", path_and_query.trim_start_matches('/'));
+  let url = Url::parse(&format!("http://localhost{}", path_and_query))
+    .unwrap_or_else(|err| panic!("invalid canned request {:?}: {}", path_and_query, err));
+
+  request_key(&url)
+}
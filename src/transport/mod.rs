@@ -0,0 +1,71 @@
+use futures::future::BoxFuture;
+use reqwest::{Method, StatusCode, header::HeaderMap};
+use url::{Url, form_urlencoded};
+
+use crate::Error;
+
+mod cassette;
+mod http;
+mod memory;
+pub use cassette::{Cassette, CassetteTransport, Interaction, RecordedRequest, RecordedResponse};
+pub use http::ReqwestTransport;
+pub use memory::MemoryTransport;
+
+/// Sends requests built by `KewClient` and hands back the raw response.
+///
+/// Status codes are interpreted by the client, so implementations should only
+/// return an error when no response could be obtained at all.
+pub trait Transport: std::fmt::Debug + Send + Sync {
+  fn send(&self, request: HttpRequest) -> BoxFuture<'_, Result<HttpResponse, Error>>;
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpRequest {
+  pub method: Method,
+  pub url: Url,
+  pub headers: HeaderMap,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+  pub status: StatusCode,
+  pub headers: HeaderMap,
+  pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+  pub fn new(status: StatusCode, body: impl Into<Vec<u8>>) -> Self {
+    Self {
+      status,
+      headers: HeaderMap::new(),
+      body: body.into(),
+    }
+  }
+
+  pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+    Self::new(StatusCode::OK, body)
+  }
+}
+
+/// The path and query of `url`, with the query parameters sorted so that the
+/// same request always produces the same key.
+pub(crate) fn request_key(url: &Url) -> String {
+  let mut pairs = url.query_pairs().into_owned().collect::<Vec<_>>();
+  pairs.sort();
+
+  if pairs.is_empty() {
+    return url.path().to_owned();
+  }
+
+  let query = form_urlencoded::Serializer::new(String::new())
+    .extend_pairs(pairs)
+    .finish();
+
+  format!("{}?{}", url.path(), query)
+}
+
+/// `request_key` with the scheme and host in front, for keys that have to
+/// tell IPNI and POWO apart.
+pub(crate) fn url_key(url: &Url) -> String {
+  format!("{}{}", url.origin().ascii_serialization(), request_key(url))
+}